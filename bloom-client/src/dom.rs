@@ -1,4 +1,5 @@
 use std::{
+    any::{Any, TypeId},
     collections::HashMap,
     future::poll_fn,
     sync::{
@@ -8,17 +9,24 @@ use std::{
     task::Poll,
 };
 
-use bloom_core::ObjectModel;
-use bloom_html::HtmlNode;
+use bloom_core::{ObjectModel, SerializedState};
+use bloom_html::{
+    is_boolean_attribute, is_rendered_attribute, Head, HtmlNode, PropertyValue,
+    CLIENT_RENDER_MARKER, FRAGMENT_END_MARKER, FRAGMENT_START_MARKER, SERIALIZED_STATE_ID,
+    TEXT_MARKER,
+};
 use futures_util::Future;
 use weak_table::PtrWeakKeyHashMap;
 use web_sys::{
     console,
-    wasm_bindgen::{closure::Closure, JsCast},
-    window, Comment, Element, Node, Text,
+    js_sys::Reflect,
+    wasm_bindgen::{closure::Closure, JsCast, JsValue},
+    window, Comment, CssStyleDeclaration, Element, Node, Text,
 };
 
-fn document() -> web_sys::Document {
+use crate::{events::Events, head::reconcile_head, DomError};
+
+pub(crate) fn document() -> web_sys::Document {
     window()
         .expect("Window not found")
         .document()
@@ -26,40 +34,26 @@ fn document() -> web_sys::Document {
 }
 
 enum NodeState {
-    Element {
-        node: Element,
-        callbacks: HashMap<String, Closure<dyn Fn(web_sys::Event)>>,
-    },
-    Text {
-        node: Text,
-    },
-    Comment {
-        node: Comment,
-    },
+    Element { node: Element },
+    Text { node: Text },
+    Comment { node: Comment },
 }
 
 impl NodeState {
-    fn create(node: &Arc<HtmlNode>) -> Self {
-        match node.as_ref() {
+    fn create(node: &Arc<HtmlNode>) -> Result<Self, DomError> {
+        Ok(match node.as_ref() {
             HtmlNode::Element(element) => {
-                let dom_node = document()
-                    .create_element(element.tag_name())
-                    .expect("Element not created");
-
-                for (key, value) in element.attributes() {
-                    dom_node
-                        .set_attribute(key, value)
-                        .expect("Failed to set attribute");
+                let dom_node = document().create_element(element.tag_name())?;
+
+                for (key, value) in element.rendered_attributes() {
+                    dom_node.set_attribute(key, &value)?;
                 }
 
                 if let Some(dom_ref) = element.dom_ref() {
                     dom_ref.set(dom_node.clone().into());
                 }
 
-                Self::Element {
-                    callbacks: Self::setup_callbacks(node, &dom_node),
-                    node: dom_node,
-                }
+                Self::Element { node: dom_node }
             }
             HtmlNode::Text(text) => {
                 let text_node = document().create_text_node(text);
@@ -75,32 +69,29 @@ impl NodeState {
                 }
                 Self::Comment { node: comment_node }
             }
-        }
+        })
     }
 
-    fn hydrate(node: &Arc<HtmlNode>, dom_node: Node) -> Self {
-        match node.as_ref() {
+    fn hydrate(node: &Arc<HtmlNode>, dom_node: Node) -> Result<Self, DomError> {
+        Ok(match node.as_ref() {
             HtmlNode::Element(element) => {
                 if let Some(dom_ref) = element.dom_ref() {
                     dom_ref.set(dom_node.clone());
                 }
 
-                let dom_node: web_sys::Element = dom_node
-                    .dyn_into()
-                    .expect("Expected Element, received Text");
+                let dom_node: web_sys::Element = dom_node.dyn_into().map_err(|_| {
+                    DomError::HydrationMismatch(format!(
+                        "Expected Element <{}>",
+                        element.tag_name()
+                    ))
+                })?;
 
-                Self::Element {
-                    callbacks: Self::setup_callbacks(
-                        node,
-                        dom_node.dyn_ref().expect("Expected Element, received Text"),
-                    ),
-                    node: dom_node,
-                }
+                Self::Element { node: dom_node }
             }
             HtmlNode::Text(_) => Self::Text {
                 node: dom_node
                     .dyn_into()
-                    .expect("Expected Text, received Element"),
+                    .map_err(|_| DomError::HydrationMismatch("Expected Text".to_string()))?,
             },
             HtmlNode::Comment(comment) => {
                 if let Some(dom_ref) = comment.dom_ref() {
@@ -110,54 +101,15 @@ impl NodeState {
                 Self::Comment {
                     node: dom_node
                         .dyn_into()
-                        .expect("Expected Comment, received Node"),
+                        .map_err(|_| DomError::HydrationMismatch("Expected Comment".to_string()))?,
                 }
             }
-        }
-    }
-
-    fn setup_callbacks(
-        node: &Arc<HtmlNode>,
-        dom_node: &Element,
-    ) -> HashMap<String, Closure<dyn Fn(web_sys::Event)>> {
-        let mut registered_callbacks = HashMap::new();
-        for key in node
-            .as_element()
-            .expect("Cannot setup callbacks for text node")
-            .callbacks()
-            .keys()
-        {
-            let node = node.clone();
-            let cloned_key = key.clone();
-            let closure: Closure<dyn Fn(web_sys::Event)> =
-                Closure::new(move |event: web_sys::Event| {
-                    if let Some(callback) = node
-                        .as_element()
-                        .and_then(|el| el.callbacks().get(&cloned_key))
-                    {
-                        callback(event);
-                    }
-                });
-            dom_node
-                .add_event_listener_with_callback(key, closure.as_ref().unchecked_ref())
-                .expect("Failed to add event listener");
-            registered_callbacks.insert(key.clone(), closure);
-        }
-        registered_callbacks
+        })
     }
 
-    fn clear_callbacks(self) -> Node {
+    fn into_node(self) -> Node {
         match self {
-            Self::Element { node, callbacks } => {
-                for (key, closure) in callbacks.into_iter() {
-                    node.remove_event_listener_with_callback(
-                        &key,
-                        closure.as_ref().unchecked_ref(),
-                    )
-                    .expect("Failed to remove event listener");
-                }
-                node.into()
-            }
+            Self::Element { node } => node.into(),
             Self::Text { node } => node.into(),
             Self::Comment { node } => node.into(),
         }
@@ -172,9 +124,419 @@ impl NodeState {
     }
 }
 
+/// Update the attributes of a DOM element while keeping their order.
+/// Existing attributes keep their position when their value is changed,
+/// so the attributes are only re-added if their order would not match otherwise.
+pub(crate) fn update_attributes(
+    dom_element: &Element,
+    element: &bloom_html::HtmlElement,
+) -> Result<(), DomError> {
+    let attributes: Vec<_> = element.rendered_attributes().collect();
+    let mut current = Vec::new();
+    for name in dom_element.get_attribute_names() {
+        let name = name.as_string().expect("Attribute name is not a string");
+        if attributes.iter().any(|(key, _)| *key == name) {
+            current.push(name);
+        } else {
+            dom_element.remove_attribute(&name)?;
+        }
+    }
+
+    // New attributes are appended, so the remaining ones have to come first
+    let in_order = attributes
+        .iter()
+        .map(|(key, _)| *key)
+        .take(current.len())
+        .eq(current.iter().map(String::as_str));
+
+    if !in_order {
+        for name in current {
+            dom_element.remove_attribute(&name)?;
+        }
+    }
+
+    for (key, value) in attributes {
+        if dom_element.get_attribute(key).as_deref() != Some(&value) {
+            dom_element.set_attribute(key, &value)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// The value of the attribute in the DOM, boolean attributes set to "false" are left out.
+fn rendered_attribute<'a>(element: &'a bloom_html::HtmlElement, key: &str) -> Option<&'a str> {
+    element
+        .attribute(key)
+        .filter(|value| is_rendered_attribute(key, value))
+}
+
+/// Update the attributes of a DOM element that differ between the previous and the next element.
+/// Only changed attributes are set and only removed ones are deleted,
+/// attributes the tree does not manage are left alone.
+fn diff_attributes(
+    dom_element: &Element,
+    previous: &bloom_html::HtmlElement,
+    next: &bloom_html::HtmlElement,
+) -> Result<(), DomError> {
+    if previous.attributes() != next.attributes() {
+        // New attributes are appended, so the remaining ones have to keep their order
+        let retained: Vec<&str> = previous
+            .attributes()
+            .keys()
+            .filter(|key| next.attributes().contains_key(key))
+            .collect();
+        if !next
+            .attributes()
+            .keys()
+            .take(retained.len())
+            .eq(retained.iter().copied())
+        {
+            return update_attributes(dom_element, next);
+        }
+
+        for key in previous.attributes().keys() {
+            if !is_list_attribute(key)
+                && rendered_attribute(previous, key).is_some()
+                && rendered_attribute(next, key).is_none()
+            {
+                dom_element.remove_attribute(key)?;
+            }
+        }
+        for (key, value) in next.attributes() {
+            if !is_list_attribute(key)
+                && is_rendered_attribute(key, value)
+                && rendered_attribute(previous, key) != Some(value)
+            {
+                dom_element.set_attribute(key, value)?;
+            }
+        }
+    }
+
+    diff_classes(dom_element, previous, next)?;
+    diff_styles(dom_element, previous, next)
+}
+
+/// The `class` and `style` attributes also contain the classes and styles set separately,
+/// so they are diffed on their own.
+fn is_list_attribute(name: &str) -> bool {
+    name == "class" || name == "style"
+}
+
+/// Add and remove single classes via `classList`,
+/// unless the `class` attribute itself changed.
+fn diff_classes(
+    dom_element: &Element,
+    previous: &bloom_html::HtmlElement,
+    next: &bloom_html::HtmlElement,
+) -> Result<(), DomError> {
+    if previous.attribute("class") != next.attribute("class") {
+        return match next.class_name() {
+            Some(class_name) => dom_element.set_attribute("class", &class_name),
+            None => dom_element.remove_attribute("class"),
+        }
+        .map_err(DomError::from);
+    }
+    if previous.classes() == next.classes() {
+        return Ok(());
+    }
+
+    let in_attribute = |class: &str| {
+        next.attribute("class")
+            .is_some_and(|attribute| attribute.split_whitespace().any(|c| c == class))
+    };
+    let class_list = dom_element.class_list();
+    for class in previous.classes().iter() {
+        if !next.classes().contains(class) && !in_attribute(class) {
+            class_list.remove_1(class)?;
+        }
+    }
+    for class in next.classes().iter() {
+        if !previous.classes().contains(class) {
+            class_list.add_1(class)?;
+        }
+    }
+    Ok(())
+}
+
+/// Set and remove single style properties via `style.setProperty`,
+/// unless the `style` attribute itself changed.
+fn diff_styles(
+    dom_element: &Element,
+    previous: &bloom_html::HtmlElement,
+    next: &bloom_html::HtmlElement,
+) -> Result<(), DomError> {
+    if previous.attribute("style") != next.attribute("style") {
+        return match next.style_text() {
+            Some(style) => dom_element.set_attribute("style", &style),
+            None => dom_element.remove_attribute("style"),
+        }
+        .map_err(DomError::from);
+    }
+    if previous.styles() == next.styles() {
+        return Ok(());
+    }
+
+    let style: CssStyleDeclaration = Reflect::get(dom_element, &JsValue::from_str("style"))?
+        .dyn_into()
+        .map_err(|_| DomError::NodeNotFound("Style declaration not found"))?;
+    for (key, _) in previous.styles() {
+        if !next.styles().contains_key(key) {
+            style.remove_property(key)?;
+        }
+    }
+    for (key, value) in next.styles() {
+        if previous.styles().get(key) != Some(value) {
+            style.set_property(key, value)?;
+        }
+    }
+    Ok(())
+}
+
+/// Set the DOM properties of an element that differ from their current value,
+/// e.G. the value of an input the user typed into.
+fn update_properties(
+    dom_element: &Element,
+    element: &bloom_html::HtmlElement,
+) -> Result<(), DomError> {
+    for (key, value) in element.properties() {
+        let key = JsValue::from_str(key);
+        let value = match value {
+            PropertyValue::Bool(value) => JsValue::from_bool(*value),
+            PropertyValue::String(value) => JsValue::from_str(value),
+            PropertyValue::Number(value) => JsValue::from_f64(*value),
+        };
+        if Reflect::get(dom_element, &key)? != value {
+            Reflect::set(dom_element, &key, &value)?;
+        }
+    }
+    Ok(())
+}
+
+fn is_marker(node: &Node, markers: &[&str]) -> bool {
+    node.node_type() == Node::COMMENT_NODE
+        && node
+            .text_content()
+            .is_some_and(|text| markers.contains(&text.as_str()))
+}
+
+/// Find the DOM node at the given index to hydrate a node with.
+/// The markers bloom-ssr puts around fragments and between text nodes are removed on the way.
+fn hydration_node(parent: &Node, index: u32, node: &HtmlNode) -> Result<Option<Node>, DomError> {
+    let children = parent.child_nodes();
+
+    while let Some(existing) = children.item(index) {
+        if !is_marker(&existing, &[FRAGMENT_START_MARKER, FRAGMENT_END_MARKER]) {
+            break;
+        }
+        parent.remove_child(&existing)?;
+    }
+
+    let text = match node {
+        HtmlNode::Text(text) => text,
+        _ => return Ok(children.item(index)),
+    };
+
+    if let Some(existing) = children
+        .item(index)
+        .filter(|existing| is_marker(existing, &[TEXT_MARKER]))
+    {
+        parent.remove_child(&existing)?;
+    }
+
+    match children.item(index) {
+        Some(existing) if existing.node_type() == Node::TEXT_NODE && !text.is_empty() => {
+            // Adjacent text nodes have been merged by the parser if there was no marker in between,
+            // e.G. within a <textarea>
+            let content = existing.text_content().unwrap_or_default();
+            if content.len() > text.len() && content.starts_with(text.as_str()) {
+                existing
+                    .unchecked_ref::<Text>()
+                    .split_text(text.encode_utf16().count() as u32)?;
+            }
+            Ok(Some(existing))
+        }
+        sibling if text.is_empty() => {
+            // Empty text nodes do not exist in the parsed document
+            let text_node: Node = document().create_text_node("").into();
+            parent.insert_before(&text_node, sibling.as_ref())?;
+            Ok(Some(text_node))
+        }
+        existing => Ok(existing),
+    }
+}
+
+/// Verify that a server-rendered DOM node matches the node to hydrate it with.
+fn check_hydration(node: &HtmlNode, existing: &Node) -> Result<(), String> {
+    match node {
+        HtmlNode::Element(element) => {
+            let existing = existing.dyn_ref::<Element>().ok_or_else(|| {
+                format!(
+                    "expected <{}>, found {}",
+                    element.tag_name(),
+                    existing.node_name()
+                )
+            })?;
+            let tag_name = existing.tag_name().to_lowercase();
+            if tag_name != element.tag_name() {
+                return Err(format!(
+                    "expected <{}>, found <{}>",
+                    element.tag_name(),
+                    tag_name
+                ));
+            }
+            // Attributes that are not rendered by the tree are ignored,
+            // e.G. those added by browser extensions
+            for (key, value) in element.rendered_attributes() {
+                let existing_value = existing.get_attribute(key);
+                let matches = if is_boolean_attribute(key) {
+                    existing_value.is_some()
+                } else {
+                    existing_value.as_deref() == Some(&value)
+                };
+                if !matches {
+                    return Err(format!(
+                        "expected {}=\"{}\", found {:?}",
+                        key, value, existing_value
+                    ));
+                }
+            }
+            Ok(())
+        }
+        HtmlNode::Text(text) => {
+            if existing.node_type() != Node::TEXT_NODE {
+                return Err(format!("expected text, found {}", existing.node_name()));
+            }
+            let content = existing.text_content().unwrap_or_default();
+            if &content != text {
+                return Err(format!("expected text {:?}, found {:?}", text, content));
+            }
+            Ok(())
+        }
+        HtmlNode::Comment(_) => {
+            if existing.node_type() != Node::COMMENT_NODE {
+                return Err(format!("expected comment, found {}", existing.node_name()));
+            }
+            Ok(())
+        }
+    }
+}
+
+/// A readable path to a DOM node for error messages, e.G. `html > body[1] > div[0] > #text[2]`.
+fn dom_path(node: &Node) -> String {
+    let mut segments = Vec::new();
+    let mut current = node.clone();
+    while let Some(parent) = current.parent_node() {
+        let name = current.node_name().to_lowercase();
+        if parent.node_type() == Node::DOCUMENT_NODE {
+            segments.push(name);
+            break;
+        }
+        let siblings = parent.child_nodes();
+        let index = (0..siblings.length())
+            .find(|index| siblings.item(*index).as_ref() == Some(&current))
+            .unwrap_or_default();
+        segments.push(format!("{}[{}]", name, index));
+        current = parent;
+    }
+    segments.reverse();
+    segments.join(" > ")
+}
+
+/// Remove the server-rendered nodes that have not been matched during hydration.
+fn remove_unmatched(parent: &Node, index: u32) -> Result<(), DomError> {
+    let children = parent.child_nodes();
+    // The script that comes with a template of bloom-ssr
+    let mut after_template = false;
+    while let Some(child) = children.item(index) {
+        let is_template = is_ssr_template(&child);
+        let expected = is_marker(
+            &child,
+            &[
+                FRAGMENT_START_MARKER,
+                FRAGMENT_END_MARKER,
+                TEXT_MARKER,
+                CLIENT_RENDER_MARKER,
+            ],
+        ) || is_template
+            || is_serialized_state(&child)
+            || (after_template && child.node_name() == "SCRIPT")
+            // bloom-ssr renders the value property of a textarea as its content
+            || (parent.node_name() == "TEXTAREA" && child.node_type() == Node::TEXT_NODE);
+        after_template = is_template;
+        if !expected {
+            console::warn_1(
+                &format!(
+                    "Hydration mismatch at {}: unexpected node, removing it",
+                    dom_path(&child)
+                )
+                .into(),
+            );
+        }
+        parent.remove_child(&child)?;
+    }
+    Ok(())
+}
+
+/// Templates that bloom-ssr streams in along with a script, e.G. for suspense boundaries
+fn is_ssr_template(node: &Node) -> bool {
+    node.node_name() == "TEMPLATE"
+        && node
+            .dyn_ref::<Element>()
+            .is_some_and(|element| element.id().starts_with("bloom-"))
+}
+
+fn is_serialized_state(node: &Node) -> bool {
+    node.node_name() == "SCRIPT"
+        && node
+            .dyn_ref::<Element>()
+            .is_some_and(|element| element.id() == SERIALIZED_STATE_ID)
+}
+
+/// Remove the markers of fragments that did not contain any nodes
+/// or that were at the end of their parent.
+fn remove_markers(parent: &Node) -> Result<(), DomError> {
+    let children = parent.child_nodes();
+    let mut index = 0;
+    while let Some(child) = children.item(index) {
+        if is_marker(
+            &child,
+            &[
+                FRAGMENT_START_MARKER,
+                FRAGMENT_END_MARKER,
+                TEXT_MARKER,
+                CLIENT_RENDER_MARKER,
+            ],
+        ) {
+            parent.remove_child(&child)?;
+        } else {
+            index += 1;
+        }
+    }
+    Ok(())
+}
+
+/// How a node was mounted during hydration.
+#[derive(Clone, Copy, PartialEq)]
+enum Mounted {
+    /// The server-rendered node has been adopted
+    Adopted,
+    /// The server-rendered node did not match, so the node and its children are rendered on the client
+    ClientRendered,
+}
+
 pub(crate) struct Dom {
     nodes: PtrWeakKeyHashMap<Weak<HtmlNode>, NodeState>,
     hydration_state: Option<PtrWeakKeyHashMap<Weak<HtmlNode>, u32>>,
+    mounted: PtrWeakKeyHashMap<Weak<HtmlNode>, Mounted>,
+    serialized_state: Option<SerializedState>,
+    /// The head managed by this tree and the version of its tags that was last reconciled
+    head: Option<(Head, u64)>,
+    context: Arc<HashMap<TypeId, Arc<dyn Any + Send + Sync>>>,
+    /// Elements whose properties are set once their children exist,
+    /// e.G. the value of a `<select>` can only be set once its options are there.
+    pending_properties: Vec<Weak<HtmlNode>>,
+    events: Events,
 }
 
 impl Dom {
@@ -182,6 +544,12 @@ impl Dom {
         Self {
             nodes: PtrWeakKeyHashMap::new(),
             hydration_state: None,
+            mounted: PtrWeakKeyHashMap::new(),
+            serialized_state: None,
+            head: None,
+            context: Arc::default(),
+            pending_properties: Vec::new(),
+            events: Events::default(),
         }
     }
 
@@ -189,12 +557,80 @@ impl Dom {
         Self {
             nodes: PtrWeakKeyHashMap::new(),
             hydration_state: Some(PtrWeakKeyHashMap::new()),
+            mounted: PtrWeakKeyHashMap::new(),
+            serialized_state: None,
+            head: None,
+            context: Arc::default(),
+            pending_properties: Vec::new(),
+            events: Events::default(),
         }
     }
 
-    pub(crate) fn register(&mut self, node: &Arc<HtmlNode>, dom_node: Node) {
-        self.nodes
-            .insert(node.clone(), NodeState::hydrate(node, dom_node));
+    /// Seed `use_serialized_state` hooks with the values rendered on the server.
+    /// The context map is kept after hydration so components are not re-rendered
+    /// because of a context change.
+    pub(crate) fn with_serialized_state(mut self, serialized_state: SerializedState) -> Self {
+        self.provide(serialized_state.clone());
+        self.serialized_state = Some(serialized_state);
+        self
+    }
+
+    /// Reconcile the tags set via `use_head` into `document.head`.
+    /// Only the root tree manages the head, nested trees inherit it via context.
+    pub(crate) fn with_head(mut self) -> Self {
+        let head = Head::new();
+        self.provide(head.clone());
+        self.head = Some((head, 0));
+        self
+    }
+
+    fn queue_properties(&mut self, node: &Arc<HtmlNode>) {
+        if node
+            .as_element()
+            .is_some_and(|element| !element.properties().is_empty())
+        {
+            self.pending_properties.push(Arc::downgrade(node));
+        }
+    }
+
+    fn apply_properties(&mut self) {
+        for node in std::mem::take(&mut self.pending_properties) {
+            let node = match node.upgrade() {
+                Some(node) => node,
+                None => continue,
+            };
+            if let (Some(element), Some(NodeState::Element { node: dom_node, .. })) =
+                (node.as_element(), self.nodes.get(&node))
+            {
+                if let Err(error) = update_properties(dom_node, element) {
+                    console::error_1(&format!("Failed to set properties: {:?}", error).into());
+                }
+            }
+        }
+    }
+
+    fn provide<T>(&mut self, value: T)
+    where
+        T: Send + Sync + 'static,
+    {
+        Arc::make_mut(&mut self.context).insert(TypeId::of::<T>(), Arc::new(value));
+    }
+
+    pub(crate) fn register(
+        &mut self,
+        node: &Arc<HtmlNode>,
+        dom_node: Node,
+    ) -> Result<(), DomError> {
+        self.events.set_root(&dom_node);
+        self.insert_state(node, NodeState::hydrate(node, dom_node)?)
+    }
+
+    fn insert_state(&mut self, node: &Arc<HtmlNode>, state: NodeState) -> Result<(), DomError> {
+        if let NodeState::Element { node: dom_node } = &state {
+            self.events.register(node, dom_node)?;
+        }
+        self.nodes.insert(node.clone(), state);
+        Ok(())
     }
 
     pub(crate) fn set_hydration_index(&mut self, node: Arc<HtmlNode>, index: u32) {
@@ -206,57 +642,139 @@ impl Dom {
 
 impl ObjectModel for Dom {
     type Node = HtmlNode;
+    type Error = DomError;
 
     fn create(
         &mut self,
         node: &std::sync::Arc<Self::Node>,
         parent: &std::sync::Arc<Self::Node>,
         sibling: &Option<std::sync::Arc<Self::Node>>,
-    ) {
+    ) -> Result<(), DomError> {
         console::log_1(&format!("Create {:?}", node).into());
-        let parent_state = self.nodes.get(parent).expect("Parent not found");
+        self.queue_properties(node);
+        let parent_state = self
+            .nodes
+            .get(parent)
+            .ok_or(DomError::NodeNotFound("Parent not found"))?;
 
         if let Some(hydration_state) = &mut self.hydration_state {
-            console::log_1(&"Hydrate".into());
-            let hydration_index = hydration_state.get(parent).cloned().unwrap_or(0);
+            if self.mounted.get(parent) == Some(&Mounted::ClientRendered) {
+                self.mounted.insert(node.clone(), Mounted::ClientRendered);
+            } else {
+                console::log_1(&"Hydrate".into());
+                let parent_node = parent_state.node().clone();
+                let hydration_index = hydration_state.get(parent).cloned().unwrap_or(0);
+                hydration_state.insert(parent.clone(), hydration_index + 1);
+
+                let existing = hydration_node(&parent_node, hydration_index, node)?;
+                if let Some(marker) = existing
+                    .as_ref()
+                    .filter(|existing| is_marker(existing, &[CLIENT_RENDER_MARKER]))
+                {
+                    // The server stopped rendering here, e.G. at its deadline,
+                    // so this node and its following siblings are rendered on the client
+                    let state = NodeState::create(node)?;
+                    parent_node.replace_child(state.node(), marker)?;
+                    self.insert_state(node, state)?;
+                    self.mounted.insert(node.clone(), Mounted::ClientRendered);
+                    self.mounted.insert(parent.clone(), Mounted::ClientRendered);
+                    return Ok(());
+                }
+
+                let mismatch = match existing {
+                    Some(existing) => match check_hydration(node, &existing) {
+                        Ok(()) => {
+                            self.insert_state(node, NodeState::hydrate(node, existing)?)?;
+                            self.mounted.insert(node.clone(), Mounted::Adopted);
+                            return Ok(());
+                        }
+                        Err(mismatch) => Some((existing, mismatch)),
+                    },
+                    None => None,
+                };
 
-            let existing_node = parent_state
-                .node()
-                .child_nodes()
-                .item(hydration_index)
-                .expect("Hydration mismatch");
+                // Discard the server-rendered subtree and render it on the client instead
+                let message = match &mismatch {
+                    Some((existing, mismatch)) => format!("{}: {}", dom_path(existing), mismatch),
+                    None => format!(
+                        "{} > [{}]: missing node",
+                        dom_path(&parent_node),
+                        hydration_index
+                    ),
+                };
+                console::warn_1(
+                    &format!(
+                        "Hydration mismatch at {}, rendering on the client instead",
+                        message
+                    )
+                    .into(),
+                );
 
-            hydration_state.insert(parent.clone(), hydration_index + 1);
-            self.nodes
-                .insert(node.clone(), NodeState::hydrate(node, existing_node));
-            return;
+                let state = NodeState::create(node)?;
+                match mismatch {
+                    Some((existing, _)) => {
+                        parent_node.replace_child(state.node(), &existing)?;
+                    }
+                    None => {
+                        parent_node.append_child(state.node())?;
+                    }
+                }
+                self.insert_state(node, state)?;
+                self.mounted.insert(node.clone(), Mounted::ClientRendered);
+                return Ok(());
+            }
         }
 
-        let sibling_node = sibling
-            .as_ref()
-            .map(|sibling| self.nodes.get(&sibling).expect("Sibling not found").node());
+        let sibling_node = match sibling {
+            Some(sibling) => Some(
+                self.nodes
+                    .get(sibling)
+                    .ok_or(DomError::NodeNotFound("Sibling not found"))?
+                    .node(),
+            ),
+            None => None,
+        };
 
-        let state = NodeState::create(node);
+        let state = NodeState::create(node)?;
 
         parent_state
             .node()
-            .insert_before(state.node(), sibling_node)
-            .expect("Failed to insert node");
+            .insert_before(state.node(), sibling_node)?;
 
-        self.nodes.insert(node.clone(), state);
+        self.insert_state(node, state)?;
+        Ok(())
     }
 
-    fn remove(&mut self, node: &std::sync::Arc<Self::Node>, parent: &std::sync::Arc<Self::Node>) {
-        let parent_node = self.nodes.get(parent).expect("Parent not found").node();
-        let current_node = self.nodes.get(node).expect("Node not found").node();
-        parent_node
-            .remove_child(current_node)
-            .expect("Failed to remove child node");
+    fn remove(
+        &mut self,
+        node: &std::sync::Arc<Self::Node>,
+        parent: &std::sync::Arc<Self::Node>,
+    ) -> Result<(), DomError> {
+        let parent_node = self
+            .nodes
+            .get(parent)
+            .ok_or(DomError::NodeNotFound("Parent not found"))?
+            .node();
+        let current_node = self
+            .nodes
+            .get(node)
+            .ok_or(DomError::NodeNotFound("Node not found"))?
+            .node();
+        parent_node.remove_child(current_node)?;
+        Ok(())
     }
 
-    fn update(&mut self, node: &std::sync::Arc<Self::Node>, next: &std::sync::Arc<Self::Node>) {
-        let current_state = self.nodes.remove(node).expect("Node not found");
-        let current_node = current_state.clear_callbacks();
+    fn update(
+        &mut self,
+        node: &std::sync::Arc<Self::Node>,
+        next: &std::sync::Arc<Self::Node>,
+    ) -> Result<(), DomError> {
+        let current_state = self
+            .nodes
+            .remove(node)
+            .ok_or(DomError::NodeNotFound("Node not found"))?;
+        let current_node = current_state.into_node();
+        self.queue_properties(next);
 
         match next.as_ref() {
             HtmlNode::Element(element) => {
@@ -270,47 +788,34 @@ impl ObjectModel for Dom {
                             )
                             .into(),
                         );
-                        let new_state = NodeState::create(next);
+                        let new_state = NodeState::create(next)?;
 
                         current_element
                             .parent_node()
-                            .expect("Failed to get parent node")
-                            .replace_child(new_state.node(), current_element)
-                            .expect("Failed to replace child node");
+                            .ok_or(DomError::NodeNotFound("Failed to get parent node"))?
+                            .replace_child(new_state.node(), current_element)?;
 
-                        self.nodes.insert(next.clone(), new_state);
+                        self.insert_state(next, new_state)?;
                     } else {
                         console::log_1(&format!("Update tag {}", element.tag_name()).into());
-                        for (key, value) in element.attributes() {
-                            current_element
-                                .set_attribute(key, value)
-                                .expect("Failed to set attribute");
+                        match node.as_element() {
+                            Some(previous) => diff_attributes(current_element, previous, element)?,
+                            None => update_attributes(current_element, element)?,
                         }
 
-                        for name in current_element.get_attribute_names() {
-                            let name = name.as_string().expect("Attribute name is not a string");
-                            if !element.attributes().contains_key(&name) {
-                                current_element
-                                    .remove_attribute(&name)
-                                    .expect("Failed to remove attribute");
-                            }
-                        }
-
-                        self.nodes
-                            .insert(next.clone(), NodeState::hydrate(node, current_node));
+                        self.insert_state(next, NodeState::hydrate(next, current_node)?)?;
                         console::log_1(&format!("Updated tag {}", element.tag_name()).into());
                     }
                 } else {
                     console::log_1(&format!("Replace {:?} -> {:?}", current_node, next).into());
-                    let new_state = NodeState::create(next);
+                    let new_state = NodeState::create(next)?;
 
                     current_node
                         .parent_node()
-                        .expect("Failed to get parent node")
-                        .replace_child(new_state.node(), &current_node)
-                        .expect("Failed to replace child node");
+                        .ok_or(DomError::NodeNotFound("Failed to get parent node"))?
+                        .replace_child(new_state.node(), &current_node)?;
 
-                    self.nodes.insert(next.clone(), new_state);
+                    self.insert_state(next, new_state)?;
                 }
             }
             HtmlNode::Text(text) => {
@@ -319,19 +824,17 @@ impl ObjectModel for Dom {
                     if current_text_node.text_content().as_ref() != Some(text) {
                         current_text_node.set_text_content(Some(text));
                     }
-                    self.nodes
-                        .insert(next.clone(), NodeState::hydrate(node, current_node));
+                    self.insert_state(next, NodeState::hydrate(next, current_node)?)?;
                 } else {
                     console::log_1(&format!("Replace text {}", text).into());
-                    let new_state = NodeState::create(next);
+                    let new_state = NodeState::create(next)?;
 
                     current_node
                         .parent_node()
-                        .expect("Failed to get parent node")
-                        .replace_child(new_state.node(), &current_node)
-                        .expect("Failed to replace child node");
+                        .ok_or(DomError::NodeNotFound("Failed to get parent node"))?
+                        .replace_child(new_state.node(), &current_node)?;
 
-                    self.nodes.insert(next.clone(), new_state);
+                    self.insert_state(next, new_state)?;
                 }
             }
             HtmlNode::Comment(comment) => {
@@ -339,26 +842,64 @@ impl ObjectModel for Dom {
                     if current_comment_node.text_content().as_ref() != Some(comment.text()) {
                         current_comment_node.set_text_content(Some(comment.text()))
                     }
-                    self.nodes
-                        .insert(next.clone(), NodeState::hydrate(node, current_node));
+                    self.insert_state(next, NodeState::hydrate(next, current_node)?)?;
                 } else {
-                    let new_state = NodeState::create(next);
+                    let new_state = NodeState::create(next)?;
 
                     current_node
                         .parent_node()
-                        .expect("Failed to get parent node")
-                        .replace_child(new_state.node(), &current_node)
-                        .expect("Failed to replace child node");
+                        .ok_or(DomError::NodeNotFound("Failed to get parent node"))?
+                        .replace_child(new_state.node(), &current_node)?;
 
-                    self.nodes.insert(next.clone(), new_state);
+                    self.insert_state(next, new_state)?;
                 }
             }
         }
+        Ok(())
+    }
+
+    fn get_context(&mut self) -> Arc<HashMap<TypeId, Arc<dyn Any + Send + Sync>>> {
+        self.context.clone()
     }
 
     fn finalize(&mut self) -> impl Future<Output = ()> {
         console::log_1(&"Finalize".into());
-        self.hydration_state = None;
+        // Setting the value of a textarea first keeps it when its server-rendered content is removed
+        self.apply_properties();
+        self.events.prune();
+        if let Some(hydration_state) = self.hydration_state.take() {
+            for (html_node, state) in self.nodes.iter() {
+                if let NodeState::Element { node, .. } = state {
+                    let result = match self.mounted.get(&html_node) {
+                        Some(Mounted::ClientRendered) => Ok(()),
+                        Some(Mounted::Adopted) => remove_unmatched(
+                            node,
+                            hydration_state.get(&html_node).cloned().unwrap_or(0),
+                        ),
+                        // Roots might contain content that is not managed by this tree
+                        None => remove_markers(node),
+                    };
+                    if let Err(error) = result {
+                        console::error_1(
+                            &format!("Failed to finish hydration: {:?}", error).into(),
+                        );
+                    }
+                }
+            }
+            self.mounted.clear();
+        }
+        if let Some(serialized_state) = self.serialized_state.take() {
+            serialized_state.clear();
+        }
+        if let Some((head, reconciled)) = self.head.as_mut() {
+            let version = head.version();
+            if version != *reconciled {
+                *reconciled = version;
+                if let Err(error) = reconcile_head(&head.tags()) {
+                    console::error_1(&format!("Failed to update head: {:?}", error).into());
+                }
+            }
+        }
         let ready = Arc::new(AtomicBool::new(false));
 
         poll_fn(move |cx| {
@@ -385,36 +926,37 @@ impl ObjectModel for Dom {
 #[cfg(test)]
 #[cfg(target_arch = "wasm32")]
 mod tests {
+    use std::sync::Mutex;
+
     use bloom_html::tag::div;
     use wasm_bindgen_test::{wasm_bindgen_test, wasm_bindgen_test_configure};
-    use web_sys::HtmlElement;
 
     use super::*;
 
     wasm_bindgen_test_configure!(run_in_browser);
 
     #[wasm_bindgen_test]
-    fn update_node() {
+    async fn update_node() {
         let mut dom = Dom::new();
 
         let dom_root: web_sys::Node = document().create_element("div").unwrap().into();
-        let root = Arc::new(div().into());
-        dom.register(&root, dom_root.clone());
-        let node = Arc::new(div().into());
-        dom.create(&node, &root, &None);
+        let root = Arc::new(div().build().into());
+        dom.register(&root, dom_root.clone()).unwrap();
+        let node = Arc::new(div().build().into());
+        dom.create(&node, &root, &None).unwrap();
         let text = Arc::new(HtmlNode::text("0".to_string()));
-        dom.create(&text, &node, &None);
-        dom.finalize();
+        dom.create(&text, &node, &None).unwrap();
+        dom.finalize().await;
 
         assert_eq!(dom_root.child_nodes().length(), 1);
         let dom_node = dom_root.child_nodes().item(0).unwrap();
         assert_eq!(dom_node.text_content().unwrap(), "0");
 
-        let next = Arc::new(div().into());
-        dom.update(&node, &next);
+        let next = Arc::new(div().build().into());
+        dom.update(&node, &next).unwrap();
         let next_text = Arc::new(HtmlNode::text("1".to_string()));
-        dom.update(&text, &next_text);
-        dom.finalize();
+        dom.update(&text, &next_text).unwrap();
+        dom.finalize().await;
 
         assert_eq!(
             dom_root.child_nodes().item(0).unwrap(),
@@ -423,7 +965,403 @@ mod tests {
         );
         assert_eq!(dom_node.text_content().unwrap(), "1");
 
-        dom.remove(&next, &root);
+        dom.remove(&next, &root).unwrap();
         assert_eq!(dom_root.child_nodes().length(), 0);
     }
+
+    #[wasm_bindgen_test]
+    async fn recover_from_hydration_mismatch() {
+        let dom_root: web_sys::Element = document().create_element("div").unwrap();
+        dom_root.set_inner_html(
+            "<span>server</span><section><!--[-->a<!--|-->b<!--]--><p>extra</p></section>",
+        );
+        let server_span = dom_root.child_nodes().item(0).unwrap();
+
+        let mut dom = Dom::hydrate();
+        let root = Arc::new(div().build().into());
+        dom.register(&root, dom_root.clone().into()).unwrap();
+
+        let node = Arc::new(div().build().into());
+        dom.create(&node, &root, &None).unwrap();
+        let text = Arc::new(HtmlNode::text("client".to_string()));
+        dom.create(&text, &node, &None).unwrap();
+        let section = Arc::new(HtmlNode::element("section").build().into());
+        dom.create(&section, &root, &None).unwrap();
+        let a = Arc::new(HtmlNode::text("a".to_string()));
+        dom.create(&a, &section, &None).unwrap();
+        let b = Arc::new(HtmlNode::text("b".to_string()));
+        dom.create(&b, &section, &None).unwrap();
+        dom.finalize().await;
+
+        assert_ne!(dom_root.child_nodes().item(0).unwrap(), server_span);
+        assert_eq!(
+            dom_root.inner_html(),
+            "<div>client</div><section>ab</section>"
+        );
+    }
+
+    #[wasm_bindgen_test]
+    async fn render_children_after_client_render_marker() {
+        let dom_root: web_sys::Element = document().create_element("div").unwrap();
+        dom_root.set_inner_html("<section><p>a</p><!--[--><!--]--><!--?--></section>");
+        let server_p = dom_root.first_child().unwrap().first_child().unwrap();
+
+        let mut dom = Dom::hydrate();
+        let root = Arc::new(div().build().into());
+        dom.register(&root, dom_root.clone().into()).unwrap();
+
+        let section = Arc::new(HtmlNode::element("section").build().into());
+        dom.create(&section, &root, &None).unwrap();
+        let p = Arc::new(HtmlNode::element("p").build().into());
+        dom.create(&p, &section, &None).unwrap();
+        let a = Arc::new(HtmlNode::text("a".to_string()));
+        dom.create(&a, &p, &None).unwrap();
+        let b = Arc::new(HtmlNode::text("b".to_string()));
+        dom.create(&b, &section, &None).unwrap();
+        let c = Arc::new(HtmlNode::text("c".to_string()));
+        dom.create(&c, &section, &None).unwrap();
+        dom.finalize().await;
+
+        assert_eq!(
+            dom_root.first_child().unwrap().first_child().unwrap(),
+            server_p
+        );
+        assert_eq!(dom_root.inner_html(), "<section><p>a</p>bc</section>");
+    }
+
+    #[wasm_bindgen_test]
+    async fn ignore_unknown_attributes_when_hydrating() {
+        let dom_root: web_sys::Element = document().create_element("div").unwrap();
+        dom_root.set_inner_html(
+            "<div id=\"a\" data-extension=\"x\">text</div><template id=\"bloom-head\"></template><script></script>",
+        );
+        let server_div = dom_root.child_nodes().item(0).unwrap();
+
+        let mut dom = Dom::hydrate();
+        let root = Arc::new(div().build().into());
+        dom.register(&root, dom_root.clone().into()).unwrap();
+
+        let node = Arc::new(div().attr("id", "a").build().into());
+        dom.create(&node, &root, &None).unwrap();
+        let text = Arc::new(HtmlNode::text("text".to_string()));
+        dom.create(&text, &node, &None).unwrap();
+        dom.finalize().await;
+
+        assert_eq!(dom_root.child_nodes().item(0).unwrap(), server_div);
+        assert_eq!(
+            dom_root.inner_html(),
+            "<div id=\"a\" data-extension=\"x\">text</div>"
+        );
+    }
+
+    #[wasm_bindgen_test]
+    async fn keep_properties_in_sync() {
+        let mut dom = Dom::new();
+
+        let dom_root: web_sys::Node = document().create_element("div").unwrap().into();
+        let root = Arc::new(div().build().into());
+        dom.register(&root, dom_root.clone()).unwrap();
+        let input = Arc::new(HtmlNode::element("input").prop("value", "a").build().into());
+        dom.create(&input, &root, &None).unwrap();
+        dom.finalize().await;
+
+        let dom_input: web_sys::HtmlInputElement =
+            dom_root.first_child().unwrap().dyn_into().unwrap();
+        assert_eq!(dom_input.value(), "a");
+        assert_eq!(dom_input.get_attribute("value"), None);
+
+        // The user types, but the state did not change
+        dom_input.set_value("ab");
+        let next = Arc::new(HtmlNode::element("input").prop("value", "a").build().into());
+        dom.update(&input, &next).unwrap();
+        dom.finalize().await;
+
+        assert_eq!(dom_input.value(), "a");
+    }
+
+    #[wasm_bindgen_test]
+    async fn delegate_events_to_root() {
+        use std::sync::atomic::AtomicU32;
+
+        let mut dom = Dom::new();
+
+        let dom_root: web_sys::Node = document().create_element("div").unwrap().into();
+        let root = Arc::new(div().build().into());
+        dom.register(&root, dom_root.clone()).unwrap();
+
+        let outer_clicks = Arc::new(AtomicU32::new(0));
+        let inner_clicks = Arc::new(AtomicU32::new(0));
+        let outer = {
+            let clicks = outer_clicks.clone();
+            Arc::new(
+                div()
+                    .on("click", move |_| {
+                        clicks.fetch_add(1, Ordering::Relaxed);
+                    })
+                    .build()
+                    .into(),
+            )
+        };
+        dom.create(&outer, &root, &None).unwrap();
+        let inner = {
+            let clicks = inner_clicks.clone();
+            Arc::new(
+                HtmlNode::element("span")
+                    .on("click", move |event: web_sys::Event| {
+                        if clicks.fetch_add(1, Ordering::Relaxed) > 0 {
+                            event.stop_propagation();
+                        }
+                    })
+                    .build()
+                    .into(),
+            )
+        };
+        dom.create(&inner, &outer, &None).unwrap();
+        dom.finalize().await;
+
+        let click = || {
+            let init = web_sys::EventInit::new();
+            init.set_bubbles(true);
+            web_sys::Event::new_with_event_init_dict("click", &init).unwrap()
+        };
+        let dom_inner = dom_root.first_child().unwrap().first_child().unwrap();
+
+        dom_inner.dispatch_event(&click()).unwrap();
+        assert_eq!(inner_clicks.load(Ordering::Relaxed), 1);
+        assert_eq!(outer_clicks.load(Ordering::Relaxed), 1);
+
+        dom_inner.dispatch_event(&click()).unwrap();
+        assert_eq!(inner_clicks.load(Ordering::Relaxed), 2);
+        assert_eq!(
+            outer_clicks.load(Ordering::Relaxed),
+            1,
+            "stopPropagation should stop the delegated walk"
+        );
+    }
+
+    #[wasm_bindgen_test]
+    async fn apply_event_modifiers() {
+        use std::sync::atomic::AtomicU32;
+
+        let mut dom = Dom::new();
+
+        let dom_root: web_sys::Node = document().create_element("div").unwrap().into();
+        let root = Arc::new(div().build().into());
+        dom.register(&root, dom_root.clone()).unwrap();
+
+        let clicks = Arc::new(AtomicU32::new(0));
+        let node = {
+            let clicks = clicks.clone();
+            Arc::new(
+                div()
+                    .on("click", move |_| {
+                        clicks.fetch_add(1, Ordering::Relaxed);
+                    })
+                    .modifiers(
+                        "click",
+                        bloom_html::EventOptions::new().once().prevent_default(),
+                    )
+                    .build()
+                    .into(),
+            )
+        };
+        dom.create(&node, &root, &None).unwrap();
+        dom.finalize().await;
+
+        let click = || {
+            let init = web_sys::EventInit::new();
+            init.set_bubbles(true);
+            init.set_cancelable(true);
+            web_sys::Event::new_with_event_init_dict("click", &init).unwrap()
+        };
+        let dom_node = dom_root.first_child().unwrap();
+
+        let first = click();
+        dom_node.dispatch_event(&first).unwrap();
+        assert!(first.default_prevented());
+
+        let second = click();
+        dom_node.dispatch_event(&second).unwrap();
+        assert!(!second.default_prevented());
+        assert_eq!(clicks.load(Ordering::Relaxed), 1);
+    }
+
+    #[wasm_bindgen_test]
+    async fn stop_propagation_between_passive_and_active_handlers() {
+        use std::sync::atomic::AtomicU32;
+
+        let mut dom = Dom::new();
+
+        let dom_root: web_sys::Node = document().create_element("div").unwrap().into();
+        let root = Arc::new(div().build().into());
+        dom.register(&root, dom_root.clone()).unwrap();
+
+        let outer_clicks = Arc::new(AtomicU32::new(0));
+        let target_clicks = Arc::new(AtomicU32::new(0));
+        let passive_counter = |clicks: &Arc<AtomicU32>| {
+            let clicks = clicks.clone();
+            Arc::new(
+                div()
+                    .on("click", move |_| {
+                        clicks.fetch_add(1, Ordering::Relaxed);
+                    })
+                    .modifiers("click", bloom_html::EventOptions::new().passive())
+                    .build()
+                    .into(),
+            )
+        };
+        let outer = passive_counter(&outer_clicks);
+        dom.create(&outer, &root, &None).unwrap();
+        let inner = Arc::new(
+            div()
+                .on("click", |event: web_sys::Event| event.stop_propagation())
+                .build()
+                .into(),
+        );
+        dom.create(&inner, &outer, &None).unwrap();
+        let target = passive_counter(&target_clicks);
+        dom.create(&target, &inner, &None).unwrap();
+        dom.finalize().await;
+
+        let init = web_sys::EventInit::new();
+        init.set_bubbles(true);
+        let click = web_sys::Event::new_with_event_init_dict("click", &init).unwrap();
+        let dom_target = dom_root
+            .first_child()
+            .unwrap()
+            .first_child()
+            .unwrap()
+            .first_child()
+            .unwrap();
+        dom_target.dispatch_event(&click).unwrap();
+
+        assert_eq!(target_clicks.load(Ordering::Relaxed), 1);
+        assert_eq!(outer_clicks.load(Ordering::Relaxed), 0);
+    }
+
+    #[wasm_bindgen_test]
+    async fn run_capture_handlers_in_capture_phase() {
+        let mut dom = Dom::new();
+
+        let dom_root: web_sys::Node = document().create_element("div").unwrap().into();
+        let root = Arc::new(div().build().into());
+        dom.register(&root, dom_root.clone()).unwrap();
+
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let outer = {
+            let calls = calls.clone();
+            Arc::new(
+                div()
+                    .on("click", move |_| calls.lock().unwrap().push("capture"))
+                    .modifiers("click", bloom_html::EventOptions::new().capture())
+                    .build()
+                    .into(),
+            )
+        };
+        dom.create(&outer, &root, &None).unwrap();
+        let target = Arc::new(div().build().into());
+        dom.create(&target, &outer, &None).unwrap();
+        dom.finalize().await;
+
+        // A listener that is not delegated to the root
+        let dom_target = dom_root.first_child().unwrap().first_child().unwrap();
+        let native = {
+            let calls = calls.clone();
+            Closure::<dyn Fn()>::new(move || calls.lock().unwrap().push("native"))
+        };
+        dom_target
+            .add_event_listener_with_callback("click", native.as_ref().unchecked_ref())
+            .unwrap();
+
+        let init = web_sys::EventInit::new();
+        init.set_bubbles(true);
+        let click = web_sys::Event::new_with_event_init_dict("click", &init).unwrap();
+        dom_target.dispatch_event(&click).unwrap();
+
+        assert_eq!(*calls.lock().unwrap(), vec!["capture", "native"]);
+    }
+
+    #[wasm_bindgen_test]
+    async fn diff_attributes_on_update() {
+        let mut dom = Dom::new();
+
+        let dom_root: web_sys::Node = document().create_element("div").unwrap().into();
+        let root = Arc::new(div().build().into());
+        dom.register(&root, dom_root.clone()).unwrap();
+        let node = Arc::new(
+            div()
+                .attr("id", "a")
+                .attr("class", "b")
+                .attr("title", "c")
+                .build()
+                .into(),
+        );
+        dom.create(&node, &root, &None).unwrap();
+        dom.finalize().await;
+
+        let dom_node: web_sys::Element = dom_root.first_child().unwrap().dyn_into().unwrap();
+        // Attributes set outside of the tree, e.G. by a browser extension
+        dom_node.set_attribute("data-external", "x").unwrap();
+
+        let next = Arc::new(
+            div()
+                .attr("id", "a")
+                .attr("class", "d")
+                .attr("lang", "en")
+                .build()
+                .into(),
+        );
+        dom.update(&node, &next).unwrap();
+        dom.finalize().await;
+
+        assert_eq!(dom_node.get_attribute("id").as_deref(), Some("a"));
+        assert_eq!(dom_node.get_attribute("class").as_deref(), Some("d"));
+        assert_eq!(dom_node.get_attribute("title"), None);
+        assert_eq!(dom_node.get_attribute("lang").as_deref(), Some("en"));
+        assert_eq!(
+            dom_node.get_attribute("data-external").as_deref(),
+            Some("x")
+        );
+    }
+
+    #[wasm_bindgen_test]
+    async fn update_classes_and_styles() {
+        let mut dom = Dom::new();
+
+        let dom_root: web_sys::Node = document().create_element("div").unwrap().into();
+        let root = Arc::new(div().build().into());
+        dom.register(&root, dom_root.clone()).unwrap();
+        let node = Arc::new(
+            div()
+                .class("card")
+                .class("active")
+                .style("color", "red")
+                .style("margin", "0px")
+                .build()
+                .into(),
+        );
+        dom.create(&node, &root, &None).unwrap();
+        dom.finalize().await;
+
+        let dom_node: web_sys::HtmlElement = dom_root.first_child().unwrap().dyn_into().unwrap();
+        assert_eq!(dom_node.class_name(), "card active");
+        // A class toggled outside of the tree, e.G. by a transition library
+        dom_node.class_list().add_1("entering").unwrap();
+
+        let next = Arc::new(
+            div()
+                .class("card")
+                .class_if("active", false)
+                .style("color", "blue")
+                .build()
+                .into(),
+        );
+        dom.update(&node, &next).unwrap();
+        dom.finalize().await;
+
+        assert_eq!(dom_node.class_name(), "card entering");
+        let style = dom_node.style();
+        assert_eq!(style.get_property_value("color").unwrap(), "blue");
+        assert_eq!(style.get_property_value("margin").unwrap(), "");
+    }
 }
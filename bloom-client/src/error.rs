@@ -0,0 +1,19 @@
+use web_sys::wasm_bindgen::JsValue;
+
+/// The error type of the bloom-client object model.
+/// It is returned as `RenderError::Host` from the render loop.
+#[derive(Debug)]
+pub enum DomError {
+    /// A node was not registered with the DOM, e.G. the parent of a node that should be created.
+    NodeNotFound(&'static str),
+    /// The server-rendered DOM does not match the rendered element tree.
+    HydrationMismatch(String),
+    /// An exception thrown by a DOM API.
+    Js(JsValue),
+}
+
+impl From<JsValue> for DomError {
+    fn from(value: JsValue) -> Self {
+        Self::Js(value)
+    }
+}
@@ -0,0 +1,328 @@
+use std::{
+    cell::{Cell, RefCell},
+    collections::{HashMap, HashSet},
+    rc::Rc,
+    sync::{Arc, Weak},
+};
+
+use bloom_html::{EventHandler, HtmlNode};
+use web_sys::{
+    js_sys::Reflect,
+    wasm_bindgen::{closure::Closure, JsCast, JsValue},
+    AddEventListenerOptions, Element, Event, Node,
+};
+
+use crate::DomError;
+
+/// The property of a DOM element that holds the id of the [HtmlNode] it was rendered from.
+const NODE_ID_PROPERTY: &str = "__bloomNode";
+
+/// Events that do not bubble are listened for in the capture phase,
+/// so they reach the root at all.
+const NON_BUBBLING_EVENTS: &[&str] = &[
+    "abort",
+    "blur",
+    "canplay",
+    "canplaythrough",
+    "durationchange",
+    "emptied",
+    "ended",
+    "error",
+    "focus",
+    "invalid",
+    "load",
+    "loadeddata",
+    "loadedmetadata",
+    "loadstart",
+    "mouseenter",
+    "mouseleave",
+    "pause",
+    "play",
+    "playing",
+    "pointerenter",
+    "pointerleave",
+    "progress",
+    "ratechange",
+    "scroll",
+    "scrollend",
+    "seeked",
+    "seeking",
+    "stalled",
+    "suspend",
+    "timeupdate",
+    "toggle",
+    "volumechange",
+    "waiting",
+];
+
+thread_local! {
+    /// Ids are unique across all trees, so nested trees do not pick up each others nodes.
+    static NEXT_NODE_ID: Cell<u32> = const { Cell::new(0) };
+}
+
+struct Entry {
+    node: Weak<HtmlNode>,
+    /// The event types whose `once` handler has already run
+    fired: HashSet<String>,
+}
+
+type Registry = Rc<RefCell<HashMap<u32, Entry>>>;
+
+/// The event type, whether the listener is passive and whether it listens in the capture phase
+type ListenerKey = (String, bool, bool);
+
+/// The event type and whether the listener is in the capture phase
+type PhaseKey = (String, bool);
+
+/// Delegates the events of all elements in a tree to the listeners on its root,
+/// in the capture phase for capture handlers and in the bubble phase for the others.
+/// Passive handlers get a passive listener until a handler of the same event type and phase
+/// needs an active one, which then runs all handlers in order.
+/// Listeners look up the handlers by walking from the event target up to the root.
+/// Capture handlers run from the root towards the target, then the others run back up,
+/// just like the event would propagate, until a handler calls `stopPropagation`.
+/// Handlers see the root as `currentTarget`.
+#[derive(Default)]
+pub(crate) struct Events {
+    root: Option<Element>,
+    nodes: Registry,
+    /// The event types and phases with an active listener
+    active: Rc<RefCell<HashSet<PhaseKey>>>,
+    listeners: HashMap<ListenerKey, Closure<dyn Fn(Event)>>,
+}
+
+impl Events {
+    pub(crate) fn set_root(&mut self, root: &Node) {
+        if self.root.is_none() {
+            self.root = root.dyn_ref::<Element>().cloned();
+        }
+    }
+
+    /// Make the handlers of the node available to the root listeners.
+    pub(crate) fn register(
+        &mut self,
+        node: &Arc<HtmlNode>,
+        dom_node: &Element,
+    ) -> Result<(), DomError> {
+        let element = match node.as_element() {
+            Some(element) if !element.callbacks().is_empty() => element,
+            _ => return Ok(()),
+        };
+
+        let key = JsValue::from_str(NODE_ID_PROPERTY);
+        let id = match Reflect::get(dom_node, &key)?.as_f64() {
+            Some(id) => id as u32,
+            None => {
+                let id = NEXT_NODE_ID.with(|next| next.replace(next.get() + 1));
+                Reflect::set(dom_node, &key, &JsValue::from(id))?;
+                id
+            }
+        };
+
+        let listening = {
+            let mut nodes = self.nodes.borrow_mut();
+            let previous = nodes
+                .remove(&id)
+                .map(|entry| (entry.node.upgrade(), entry.fired));
+            let (previous, fired) = match previous {
+                Some((Some(previous), fired)) => (Some(previous), fired),
+                _ => (None, HashSet::new()),
+            };
+            let previous = previous.as_ref().and_then(|node| node.as_element());
+
+            // A `once` handler stays spent as long as the same handler is passed again
+            let fired = fired
+                .into_iter()
+                .filter(|event_type| {
+                    previous.and_then(|previous| previous.callbacks().get(event_type))
+                        == element.callbacks().get(event_type)
+                })
+                .collect();
+            nodes.insert(
+                id,
+                Entry {
+                    node: Arc::downgrade(node),
+                    fired,
+                },
+            );
+
+            // The root listeners are already there if the element listens to the same events
+            previous.is_some_and(|previous| {
+                previous.callbacks().len() == element.callbacks().len()
+                    && element.callbacks().iter().all(|(event_type, handler)| {
+                        previous.callbacks().get(event_type).is_some_and(|current| {
+                            current.options().passive == handler.options().passive
+                                && current.options().capture == handler.options().capture
+                        })
+                    })
+            })
+        };
+
+        if !listening {
+            for (event_type, handler) in element.callbacks() {
+                let options = handler.options();
+                self.listen(event_type, options.passive, options.capture)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn listen(&mut self, event_type: &str, passive: bool, capture: bool) -> Result<(), DomError> {
+        // Events that do not bubble only reach the root in the capture phase
+        let capture = capture || NON_BUBBLING_EVENTS.contains(&event_type);
+        let listener_key: ListenerKey = (event_type.to_string(), passive, capture);
+        if self.listeners.contains_key(&listener_key) {
+            return Ok(());
+        }
+        let root = self
+            .root
+            .clone()
+            .ok_or(DomError::NodeNotFound("Root not found"))?;
+
+        let nodes = self.nodes.clone();
+        let active = self.active.clone();
+        let root_node: Node = root.clone().into();
+        let listener: Closure<dyn Fn(Event)> = Closure::new(move |event: Event| {
+            if passive && active.borrow().contains(&(event.type_(), capture)) {
+                return;
+            }
+            dispatch(&nodes, &root_node, event, capture)
+        });
+        let options = AddEventListenerOptions::new();
+        options.set_capture(capture);
+        options.set_passive(passive);
+        root.add_event_listener_with_callback_and_add_event_listener_options(
+            event_type,
+            listener.as_ref().unchecked_ref(),
+            &options,
+        )?;
+        self.listeners.insert(listener_key, listener);
+        if !passive {
+            self.active
+                .borrow_mut()
+                .insert((event_type.to_string(), capture));
+        }
+        Ok(())
+    }
+
+    /// Forget the nodes that have been dropped.
+    pub(crate) fn prune(&self) {
+        self.nodes
+            .borrow_mut()
+            .retain(|_, entry| entry.node.strong_count() > 0);
+    }
+}
+
+impl Drop for Events {
+    fn drop(&mut self) {
+        if let Some(root) = self.root.as_ref() {
+            for ((event_type, _, capture), listener) in self.listeners.drain() {
+                let _ = root.remove_event_listener_with_callback_and_bool(
+                    &event_type,
+                    listener.as_ref().unchecked_ref(),
+                    capture,
+                );
+            }
+        }
+    }
+}
+
+struct PathItem {
+    id: u32,
+    node: Arc<HtmlNode>,
+    is_target: bool,
+}
+
+/// Runs the handlers for one root listener.
+/// In the capture phase, these are the capture handlers
+/// and, for events that do not bubble, the handlers on the target.
+/// Propagation is tracked by the handlers of this dispatch,
+/// since the native flag might have been set by other listeners on the root.
+fn dispatch(nodes: &Registry, root: &Node, event: Event, capture_phase: bool) {
+    let key = JsValue::from_str(NODE_ID_PROPERTY);
+    let target = event
+        .target()
+        .and_then(|target| target.dyn_into::<Node>().ok());
+    let mut path = Vec::new();
+    let mut current = target.clone();
+    while let Some(dom_node) = current {
+        if &dom_node == root {
+            break;
+        }
+        if let Some(id) = Reflect::get(&dom_node, &key)
+            .ok()
+            .and_then(|id| id.as_f64())
+        {
+            let id = id as u32;
+            let node = nodes
+                .borrow()
+                .get(&id)
+                .and_then(|entry| entry.node.upgrade());
+            if let Some(node) = node {
+                path.push(PathItem {
+                    id,
+                    node,
+                    is_target: Some(&dom_node) == target.as_ref(),
+                });
+            }
+        }
+        current = dom_node.parent_node();
+    }
+
+    let phases = match (capture_phase, event.bubbles()) {
+        (true, true) => &[true][..],
+        (true, false) => &[true, false][..],
+        (false, _) => &[false][..],
+    };
+    for &capture in phases {
+        let items: Box<dyn Iterator<Item = &PathItem>> = if capture {
+            Box::new(path.iter().rev())
+        } else {
+            Box::new(path.iter())
+        };
+        for item in items {
+            if !capture && !item.is_target && !event.bubbles() {
+                break;
+            }
+            if run_handler(nodes, item, &event, capture) {
+                return;
+            }
+        }
+    }
+}
+
+/// Returns whether the handler stopped propagation.
+fn run_handler(nodes: &Registry, item: &PathItem, event: &Event, capture: bool) -> bool {
+    let event_type = event.type_();
+    let handler: &EventHandler = match item
+        .node
+        .as_element()
+        .and_then(|element| element.callbacks().get(&event_type))
+    {
+        Some(handler) => handler,
+        None => return false,
+    };
+    let options = handler.options();
+    if options.capture != capture {
+        return false;
+    }
+    if options.once {
+        let first = nodes
+            .borrow_mut()
+            .get_mut(&item.id)
+            .is_some_and(|entry| entry.fired.insert(event_type));
+        if !first {
+            return false;
+        }
+    }
+    // The flag might have been set by another listener on the root
+    let was_stopped = event.cancel_bubble();
+    if options.prevent_default {
+        event.prevent_default();
+    }
+    if options.stop_propagation {
+        event.stop_propagation();
+    }
+    handler.call(event.clone());
+    options.stop_propagation || (!was_stopped && event.cancel_bubble())
+}
@@ -0,0 +1,66 @@
+use std::collections::HashMap;
+
+use bloom_html::{HeadTag, HEAD_KEY_ATTRIBUTE};
+use web_sys::{wasm_bindgen::JsCast, Element};
+
+use crate::{
+    dom::{document, update_attributes},
+    DomError,
+};
+
+fn create_tag(tag: &HeadTag) -> Result<Element, DomError> {
+    let element = document().create_element(tag.element().tag_name())?;
+    for (key, value) in tag.element().attributes() {
+        element.set_attribute(key, value)?;
+    }
+    element.set_text_content(tag.text_content());
+    Ok(element)
+}
+
+/// Bring the elements managed by `use_head` in `document.head` in line with the given tags.
+/// Tags rendered on the server are picked up by their key, so they are updated in place.
+pub(crate) fn reconcile_head(tags: &[HeadTag]) -> Result<(), DomError> {
+    let head = document()
+        .head()
+        .ok_or(DomError::NodeNotFound("Document head not found"))?;
+
+    let mut current = HashMap::new();
+    let existing = head.query_selector_all(&format!("[{}]", HEAD_KEY_ATTRIBUTE))?;
+    for index in 0..existing.length() {
+        if let Some(element) = existing
+            .item(index)
+            .and_then(|node| node.dyn_into::<Element>().ok())
+        {
+            if let Some(key) = element.get_attribute(HEAD_KEY_ATTRIBUTE) {
+                current.insert(key, element);
+            }
+        }
+    }
+
+    for tag in tags {
+        match current.remove(tag.key()) {
+            Some(element) if element.tag_name().to_lowercase() == tag.element().tag_name() => {
+                update_attributes(&element, tag.element())?;
+                if tag.text_content().is_some()
+                    && element.text_content().as_deref() != tag.text_content()
+                {
+                    element.set_text_content(tag.text_content());
+                }
+            }
+            Some(element) => {
+                let next = create_tag(tag)?;
+                head.replace_child(&next, &element)?;
+            }
+            None => {
+                let next = create_tag(tag)?;
+                head.append_child(&next)?;
+            }
+        }
+    }
+
+    for element in current.into_values() {
+        element.remove();
+    }
+
+    Ok(())
+}
@@ -0,0 +1,148 @@
+use std::cell::OnceCell;
+
+use bloom_router::{History, HistoryBackend};
+use web_sys::{
+    console,
+    wasm_bindgen::{closure::Closure, JsCast, JsValue},
+    window, Element, HtmlAnchorElement, MouseEvent,
+};
+
+use crate::DomError;
+
+/// A [HistoryBackend] that navigates using the browser's history API.
+struct BrowserHistory;
+
+impl HistoryBackend for BrowserHistory {
+    fn location(&self) -> String {
+        window()
+            .map(|window| window.location())
+            .map(|location| {
+                format!(
+                    "{}{}{}",
+                    location.pathname().unwrap_or_default(),
+                    location.search().unwrap_or_default(),
+                    location.hash().unwrap_or_default()
+                )
+            })
+            .unwrap_or_else(|| "/".to_string())
+    }
+
+    fn push(&self, location: &str) {
+        if let Some(history) = window().and_then(|window| window.history().ok()) {
+            let _ = history.push_state_with_url(&JsValue::NULL, "", Some(location));
+        }
+    }
+
+    fn replace(&self, location: &str) {
+        if let Some(history) = window().and_then(|window| window.history().ok()) {
+            let _ = history.replace_state_with_url(&JsValue::NULL, "", Some(location));
+        }
+    }
+}
+
+thread_local! {
+    static HISTORY: OnceCell<History> = const { OnceCell::new() };
+}
+
+/// The [History] of the browser window.
+/// Provide it via context to let a `bloom_router::Router` follow the browser's location:
+/// ```ignore
+/// hydrate(
+///     get_element_by_id("root").unwrap(),
+///     Provider::new(browser_history()).children(vec![rsx!(<App />)]),
+/// );
+/// ```
+/// Clicks on same-origin links are intercepted and navigate without a page load,
+/// unless the link has a `target`, a `download` attribute or `rel="external"`.
+pub fn browser_history() -> History {
+    HISTORY.with(|history| {
+        history
+            .get_or_init(|| {
+                let history = History::new(BrowserHistory);
+                if let Err(error) = listen(&history) {
+                    let msg = format!("Failed to listen for navigation: {:?}", error);
+                    console::error_1(&msg.into());
+                }
+                history
+            })
+            .clone()
+    })
+}
+
+fn listen(history: &History) -> Result<(), DomError> {
+    let window = window().ok_or(DomError::NodeNotFound("Window not found"))?;
+
+    let popstate_history = history.clone();
+    let popstate: Closure<dyn Fn(web_sys::Event)> =
+        Closure::new(move |_| popstate_history.notify());
+    window.add_event_listener_with_callback("popstate", popstate.as_ref().unchecked_ref())?;
+    popstate.forget();
+
+    let click_history = history.clone();
+    let click: Closure<dyn Fn(MouseEvent)> = Closure::new(move |event: MouseEvent| {
+        if let Some(location) = intercepted_location(&event) {
+            event.prevent_default();
+            click_history.push(&location);
+            if let Some(window) = web_sys::window() {
+                window.scroll_to_with_x_and_y(0.0, 0.0);
+            }
+        }
+    });
+    window
+        .document()
+        .ok_or(DomError::NodeNotFound("Document not found"))?
+        .add_event_listener_with_callback("click", click.as_ref().unchecked_ref())?;
+    click.forget();
+
+    Ok(())
+}
+
+/// The location to navigate to if the click should be handled by the router.
+fn intercepted_location(event: &MouseEvent) -> Option<String> {
+    if event.default_prevented()
+        || event.button() != 0
+        || event.meta_key()
+        || event.ctrl_key()
+        || event.shift_key()
+        || event.alt_key()
+    {
+        return None;
+    }
+
+    let anchor = event
+        .target()?
+        .dyn_into::<Element>()
+        .ok()?
+        .closest("a[href]")
+        .ok()??
+        .dyn_into::<HtmlAnchorElement>()
+        .ok()?;
+
+    let target = anchor.target();
+    if (!target.is_empty() && target != "_self")
+        || anchor.has_attribute("download")
+        || anchor.rel().split_whitespace().any(|rel| rel == "external")
+    {
+        return None;
+    }
+
+    let location = window()?.location();
+    if anchor.origin() != location.origin().ok()? {
+        return None;
+    }
+
+    // Let the browser scroll to anchors on the current page.
+    if !anchor.hash().is_empty()
+        && anchor.pathname() == location.pathname().ok()?
+        && anchor.search() == location.search().ok()?
+    {
+        return None;
+    }
+
+    Some(format!(
+        "{}{}{}",
+        anchor.pathname(),
+        anchor.search(),
+        anchor.hash()
+    ))
+}
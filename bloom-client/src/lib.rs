@@ -1,7 +1,7 @@
 use std::{fmt::Debug, sync::Arc};
 
-use bloom_core::{render_loop, Element};
-use bloom_html::HtmlNode;
+use bloom_core::{render_loop, render_nested_loop, Element, NestedRoot, SerializedState};
+use bloom_html::{HtmlNode, SERIALIZED_STATE_ID};
 use dom::Dom;
 use interned_str::interned;
 use spawner::WasmSpawner;
@@ -9,10 +9,18 @@ use wasm_bindgen_futures::spawn_local;
 use web_sys::{console, wasm_bindgen::JsCast, window, HtmlElement};
 
 mod dom;
+mod error;
+mod events;
+mod head;
+mod history;
 mod interned_str;
+mod local_storage;
 mod partial;
 mod spawner;
 
+pub use error::DomError;
+pub use history::browser_history;
+pub use local_storage::LocalStorage;
 pub use partial::hydrate_partial;
 
 pub fn get_element_by_id(id: &str) -> Option<HtmlElement> {
@@ -38,14 +46,18 @@ where
     E: Send + 'static + Debug,
 {
     spawn_local(async {
-        let mut dom = Dom::new();
+        let mut dom = Dom::new().with_head();
 
         let root_node = Arc::new(
             HtmlNode::element(interned(root.tag_name().to_lowercase()))
                 .build()
                 .into(),
         );
-        dom.register(&root_node, root.into());
+        if let Err(error) = dom.register(&root_node, root.into()) {
+            let msg = format!("Failed to register root node: {:?}", error);
+            console::error_1(&msg.into());
+            return;
+        }
         if let Err(error) = render_loop(root_node, element, WasmSpawner, dom).await {
             let msg = format!("Render loop error: {:?}", error);
             console::error_1(&msg.into());
@@ -54,22 +66,71 @@ where
 }
 
 /// hydrate can be used to hydrate an existing DOM from server-side rendered HTML.
+/// Values of `use_serialized_state` hooks that were embedded by bloom-ssr
+/// are used to seed the hooks on the first render.
 pub fn hydrate<E>(root: HtmlElement, element: Element<HtmlNode, E>)
 where
     E: Send + 'static + Debug,
 {
     spawn_local(async {
-        let mut dom = Dom::hydrate();
+        let mut dom = Dom::hydrate().with_head();
+        if let Some(json) =
+            get_element_by_id(SERIALIZED_STATE_ID).and_then(|script| script.text_content())
+        {
+            dom = dom.with_serialized_state(SerializedState::restore(&json));
+        }
 
         let root_node = Arc::new(
             HtmlNode::element(interned(root.tag_name().to_lowercase()))
                 .build()
                 .into(),
         );
-        dom.register(&root_node, root.into());
+        if let Err(error) = dom.register(&root_node, root.into()) {
+            let msg = format!("Failed to register root node: {:?}", error);
+            console::error_1(&msg.into());
+            return;
+        }
         if let Err(error) = render_loop(root_node, element, WasmSpawner, dom).await {
             let msg = format!("Render loop error: {:?}", error);
             console::error_1(&msg.into());
         }
     });
 }
+
+/// render_nested renders an additional tree into the given root element,
+/// e.G. for a widget, an island or a portal into an iframe.
+/// The tree inherits the context of the component that created the nested root
+/// and is re-rendered when that context changes.
+/// Rendering stops once that component is unmounted.
+/// ```ignore
+/// let nested_root = use_nested_root();
+///
+/// use_effect(nested_root, |nested_root| {
+///     render_nested(nested_root, get_element_by_id("widget").unwrap(), rsx!(<Widget />));
+/// });
+/// ```
+pub fn render_nested<E>(nested_root: NestedRoot, root: HtmlElement, element: Element<HtmlNode, E>)
+where
+    E: Send + 'static + Debug,
+{
+    spawn_local(async {
+        let mut dom = Dom::new();
+
+        let root_node = Arc::new(
+            HtmlNode::element(interned(root.tag_name().to_lowercase()))
+                .build()
+                .into(),
+        );
+        if let Err(error) = dom.register(&root_node, root.into()) {
+            let msg = format!("Failed to register root node: {:?}", error);
+            console::error_1(&msg.into());
+            return;
+        }
+        if let Err(error) =
+            render_nested_loop(nested_root, root_node, element, WasmSpawner, dom).await
+        {
+            let msg = format!("Render loop error: {:?}", error);
+            console::error_1(&msg.into());
+        }
+    });
+}
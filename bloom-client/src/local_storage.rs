@@ -0,0 +1,25 @@
+use bloom_core::StateStorage;
+use web_sys::window;
+
+/// A [StateStorage] backend for `bloom_core::use_persistent_state` that keeps values in the browser's localStorage.
+/// ```ignore
+/// Provider::new(PersistentStorage::new(LocalStorage)).children(vec![rsx!(<App />)])
+/// ```
+#[derive(Default)]
+pub struct LocalStorage;
+
+fn local_storage() -> Option<web_sys::Storage> {
+    window().and_then(|window| window.local_storage().ok().flatten())
+}
+
+impl StateStorage for LocalStorage {
+    fn get(&self, key: &str) -> Option<String> {
+        local_storage().and_then(|storage| storage.get_item(key).ok().flatten())
+    }
+
+    fn set(&self, key: &str, value: String) {
+        if let Some(storage) = local_storage() {
+            let _ = storage.set_item(key, &value);
+        }
+    }
+}
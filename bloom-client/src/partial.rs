@@ -12,7 +12,7 @@ use std::{
 use wasm_bindgen_futures::spawn_local;
 use web_sys::{console, js_sys::Array, window, Node};
 
-use crate::{dom::Dom, interned_str::interned, spawner::WasmSpawner};
+use crate::{dom::Dom, interned_str::interned, spawner::WasmSpawner, DomError};
 
 #[derive(Default)]
 struct PartialRenderingContext {
@@ -40,31 +40,32 @@ impl PartialDom {
         root: Arc<HtmlNode>,
         dom_node: Node,
         start_index: i32,
-    ) -> Self {
+    ) -> Result<Self, DomError> {
         let mut inner = Dom::hydrate();
-        inner.register(&root, dom_node);
+        inner.register(&root, dom_node)?;
         inner.set_hydration_index(root, start_index.unsigned_abs());
-        Self(inner, context_id)
+        Ok(Self(inner, context_id))
     }
 }
 
 impl ObjectModel for PartialDom {
     type Node = HtmlNode;
+    type Error = DomError;
 
     fn create(
         &mut self,
         node: &Arc<Self::Node>,
         parent: &Arc<Self::Node>,
         sibling: &Option<Arc<Self::Node>>,
-    ) {
+    ) -> Result<(), DomError> {
         self.0.create(node, parent, sibling)
     }
 
-    fn update(&mut self, node: &Arc<Self::Node>, next: &Arc<Self::Node>) {
+    fn update(&mut self, node: &Arc<Self::Node>, next: &Arc<Self::Node>) -> Result<(), DomError> {
         self.0.update(node, next)
     }
 
-    fn remove(&mut self, node: &Arc<Self::Node>, parent: &Arc<Self::Node>) {
+    fn remove(&mut self, node: &Arc<Self::Node>, parent: &Arc<Self::Node>) -> Result<(), DomError> {
         self.0.remove(node, parent)
     }
 
@@ -127,8 +128,19 @@ where
         )
         .expect("Failed to parse context id");
 
-        let dom =
-            PartialDom::hydrate_from(context_id, root.clone(), root_dom_node.into(), start_index);
+        let dom = match PartialDom::hydrate_from(
+            context_id,
+            root.clone(),
+            root_dom_node.into(),
+            start_index,
+        ) {
+            Ok(dom) => dom,
+            Err(error) => {
+                let msg = format!("Failed to register partial root: {:?}", error);
+                console::error_1(&msg.into());
+                return;
+            }
+        };
 
         if let Err(error) = render_loop(root, element, WasmSpawner, dom).await {
             let msg = format!("Render loop error: {:?}", error);
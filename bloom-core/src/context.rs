@@ -8,6 +8,11 @@ use async_context::with_async_context;
 
 use crate::{hook::Hook, Element};
 
+/// Makes a value available to all descendants of its children.
+/// Components read it using [use_context].
+/// ```ignore
+/// Provider::new(Theme::Dark).children(vec![rsx!(<App />)])
+/// ```
 pub struct Provider {
     value: Arc<dyn Any + Send + Sync>,
 }
@@ -32,16 +37,25 @@ impl Provider {
 
 pub(crate) type ContextMap = Arc<HashMap<TypeId, Arc<dyn Any + Send + Sync>>>;
 
+/// Two context maps are considered equal if they hold the very same provided values.
+pub(crate) fn context_eq(a: &ContextMap, b: &ContextMap) -> bool {
+    Arc::ptr_eq(a, b)
+        || (a.len() == b.len()
+            && a.iter().all(|(key, value)| {
+                b.get(key)
+                    .is_some_and(|other_value| Arc::ptr_eq(value, other_value))
+            }))
+}
+
 pub fn use_context<T>() -> Arc<T>
 where
-    T: Clone + Default + 'static,
+    T: Clone + Default + Send + Sync + 'static,
 {
     with_async_context(|hook: Option<&Hook>| {
         if let Some(hook) = hook {
             hook.context
                 .get(&TypeId::of::<T>())
-                .and_then(|value| value.downcast_ref::<Arc<T>>())
-                .cloned()
+                .and_then(|value| value.clone().downcast::<T>().ok())
                 .unwrap_or(Arc::new(T::default()))
         } else {
             Arc::new(T::default())
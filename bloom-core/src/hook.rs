@@ -2,9 +2,11 @@ use std::any::{Any, TypeId};
 use std::collections::HashMap;
 use std::sync::Arc;
 
-use async_channel::{bounded, Sender};
+use async_channel::{bounded, unbounded, Receiver, Sender};
 
 use crate::effect::Effect;
+use crate::serialized_state::Serializer;
+use crate::spawn::SharedSpawner;
 use crate::state::StateUpdate;
 
 pub(crate) struct Hook {
@@ -16,6 +18,12 @@ pub(crate) struct Hook {
     pub(crate) refs: HashMap<u16, Arc<dyn Any + Send + Sync + 'static>>,
     pub(crate) ref_index: u16,
     pub(crate) context: Arc<HashMap<TypeId, Arc<dyn Any + Send + Sync>>>,
+    pub(crate) path: Arc<str>,
+    pub(crate) serializers: HashMap<u16, Serializer>,
+    /// Whether the component is rendered once by render_stream
+    /// rather than continuously by render_loop.
+    pub(crate) streaming: bool,
+    pub(crate) spawner: Option<SharedSpawner>,
 }
 
 impl Hook {
@@ -25,6 +33,8 @@ impl Hook {
         state: HashMap<u16, Arc<dyn Any + Send + Sync>>,
         refs: HashMap<u16, Arc<dyn Any + Send + Sync + 'static>>,
         context: Arc<HashMap<TypeId, Arc<dyn Any + Send + Sync>>>,
+        path: Arc<str>,
+        spawner: SharedSpawner,
     ) -> Self {
         Self {
             updater,
@@ -35,14 +45,24 @@ impl Hook {
             refs,
             ref_index: 0,
             context,
+            path,
+            serializers: HashMap::new(),
+            streaming: false,
+            spawner: Some(spawner),
         }
     }
 
-    pub(crate) fn from_context(context: Arc<HashMap<TypeId, Arc<dyn Any + Send + Sync>>>) -> Self {
+    /// Creates a hook for a single render pass, e.G. for server-side rendering.
+    /// State updates during the render pass can be collected from the returned receiver.
+    pub(crate) fn from_context(
+        context: Arc<HashMap<TypeId, Arc<dyn Any + Send + Sync>>>,
+        path: Arc<str>,
+        spawner: SharedSpawner,
+    ) -> (Self, Receiver<StateUpdate>) {
         let (signal, _) = bounded(1);
-        let (updater, _) = bounded(1);
+        let (updater, updates) = unbounded();
 
-        Self {
+        let hook = Self {
             signal,
             updater,
             state: HashMap::new(),
@@ -51,6 +71,12 @@ impl Hook {
             refs: HashMap::new(),
             ref_index: 0,
             context,
-        }
+            path,
+            serializers: HashMap::new(),
+            streaming: true,
+            spawner: Some(spawner),
+        };
+
+        (hook, updates)
     }
 }
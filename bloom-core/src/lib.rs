@@ -3,21 +3,35 @@ mod context;
 mod effect;
 mod element;
 mod hook;
+mod nested_root;
 mod object_ref;
+mod persistent_state;
 mod render_loop;
 mod render_queue;
 mod render_stream;
+mod resource;
 mod result;
+mod serialized_state;
+mod spawn;
 mod state;
 mod suspense;
 
 pub use bloom_macro::component;
 pub use component::Component;
-pub use context::{_get_context, use_context};
+pub use context::{_get_context, use_context, Provider};
 pub use effect::use_effect;
 pub use element::Element;
+pub use nested_root::{render_nested_loop, use_nested_root, NestedRoot};
 pub use object_ref::{use_ref, use_ref_with_default};
-pub use render_loop::{render_loop, ObjectModel};
-pub use render_stream::{render_stream, NodeStream};
+pub use persistent_state::{
+    use_persistent_state, FileStorage, MemoryStorage, PersistentState, PersistentStorage,
+    StateStorage,
+};
+pub use render_loop::{render_loop, ObjectModel, RenderError};
+pub use render_stream::{render_stream, NodeStream, StreamItem};
+pub use resource::{use_resource, Resource};
 pub use result::Result;
+pub use serialized_state::{use_serialized_state, SerializedState};
+pub use spawn::spawn;
 pub use state::use_state;
+pub use suspense::Suspense;
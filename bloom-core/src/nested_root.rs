@@ -0,0 +1,217 @@
+use std::{
+    hash::Hash,
+    sync::{Arc, Mutex, Weak},
+};
+
+use async_channel::Sender;
+use futures_util::{task::Spawn, Future};
+
+use crate::{
+    _get_context,
+    context::{context_eq, ContextMap},
+    render_loop, use_ref_with_default, Element, ObjectModel, RenderError,
+};
+
+struct NestedRootState {
+    context: Mutex<ContextMap>,
+    subscribers: Mutex<Vec<Sender<()>>>,
+}
+
+impl NestedRootState {
+    fn set_context(&self, context: ContextMap) {
+        let mut current = self
+            .context
+            .lock()
+            .expect("Failed to lock nested root context");
+        if context_eq(&current, &context) {
+            return;
+        }
+        *current = context;
+        drop(current);
+
+        self.subscribers
+            .lock()
+            .expect("Failed to lock nested root subscribers")
+            .retain(|subscriber| {
+                let _ = subscriber.try_send(());
+                !subscriber.is_closed()
+            });
+    }
+}
+
+impl Drop for NestedRootState {
+    fn drop(&mut self) {
+        if let Ok(subscribers) = self.subscribers.get_mut() {
+            for subscriber in subscribers.drain(..) {
+                subscriber.close();
+            }
+        }
+    }
+}
+
+/// A handle to a position in a render tree that nested render loops can attach to.
+/// Obtain it using [use_nested_root] and pass it to [render_nested_loop]
+/// (or e.G. `bloom_client::render_nested`) to render a widget, an island
+/// or a portal that shares the context of the component that created the handle.
+#[derive(Clone)]
+pub struct NestedRoot(Weak<NestedRootState>);
+
+impl NestedRoot {
+    /// Whether the component that created this handle is still mounted.
+    pub fn is_mounted(&self) -> bool {
+        self.0.strong_count() > 0
+    }
+
+    fn context(&self) -> ContextMap {
+        self.0
+            .upgrade()
+            .map(|state| {
+                state
+                    .context
+                    .lock()
+                    .expect("Failed to lock nested root context")
+                    .clone()
+            })
+            .unwrap_or_default()
+    }
+
+    fn subscribe(&self, signal: Sender<()>) {
+        if let Some(state) = self.0.upgrade() {
+            state
+                .subscribers
+                .lock()
+                .expect("Failed to lock nested root subscribers")
+                .push(signal);
+        } else {
+            signal.close();
+        }
+    }
+}
+
+impl PartialEq for NestedRoot {
+    fn eq(&self, other: &Self) -> bool {
+        Weak::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Hash for NestedRoot {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.0.as_ptr().hash(state);
+    }
+}
+
+/// use_nested_root creates a handle that nested render loops can use
+/// to inherit the context of the calling component.
+/// Nested render loops re-render whenever the providers above the calling component change
+/// and stop once the calling component is unmounted.
+/// ```ignore
+/// let nested_root = use_nested_root();
+///
+/// use_effect(nested_root, |nested_root| {
+///     bloom_client::render_nested(nested_root, get_element_by_id("widget").unwrap(), rsx!(<Widget />));
+/// });
+/// ```
+pub fn use_nested_root() -> NestedRoot {
+    let context = _get_context();
+    let state = use_ref_with_default(|| NestedRootState {
+        context: Mutex::new(context.clone()),
+        subscribers: Mutex::new(Vec::new()),
+    });
+    state.set_context(context);
+    NestedRoot(Arc::downgrade(&state))
+}
+
+struct NestedObjectModel<P> {
+    inner: P,
+    nested_root: NestedRoot,
+}
+
+impl<P> ObjectModel for NestedObjectModel<P>
+where
+    P: ObjectModel,
+{
+    type Node = P::Node;
+    type Error = P::Error;
+
+    fn start(&mut self) -> impl Future<Output = ()> + Send {
+        self.inner.start()
+    }
+
+    fn create(
+        &mut self,
+        node: &Arc<Self::Node>,
+        parent: &Arc<Self::Node>,
+        sibling: &Option<Arc<Self::Node>>,
+    ) -> Result<(), Self::Error> {
+        self.inner.create(node, parent, sibling)
+    }
+
+    fn remove(
+        &mut self,
+        node: &Arc<Self::Node>,
+        parent: &Arc<Self::Node>,
+    ) -> Result<(), Self::Error> {
+        self.inner.remove(node, parent)
+    }
+
+    fn update(
+        &mut self,
+        node: &Arc<Self::Node>,
+        next: &Arc<Self::Node>,
+    ) -> Result<(), Self::Error> {
+        self.inner.update(node, next)
+    }
+
+    fn finalize(&mut self) -> impl Future<Output = ()> + Send {
+        self.inner.finalize()
+    }
+
+    fn subscribe(&mut self, signal: Sender<()>) {
+        self.inner.subscribe(signal.clone());
+        self.nested_root.subscribe(signal);
+    }
+
+    fn get_context(&mut self) -> ContextMap {
+        let context = self.nested_root.context();
+        let inner_context = self.inner.get_context();
+        if inner_context.is_empty() {
+            context
+        } else {
+            let mut merged = context.as_ref().clone();
+            merged.extend(
+                inner_context
+                    .iter()
+                    .map(|(key, value)| (*key, value.clone())),
+            );
+            Arc::new(merged)
+        }
+    }
+}
+
+/// render_nested_loop works just like [render_loop] but the rendered tree inherits
+/// the context of the component that created the [NestedRoot].
+/// The loop ends with `Ok(())` once that component is unmounted.
+pub async fn render_nested_loop<N, E, S, P>(
+    nested_root: NestedRoot,
+    root: Arc<N>,
+    element: Element<N, E>,
+    spawner: S,
+    object_model: P,
+) -> Result<(), RenderError<E, P::Error>>
+where
+    N: From<String> + Send + 'static,
+    E: Send + 'static,
+    S: Spawn + Send + 'static,
+    P: ObjectModel<Node = N>,
+{
+    render_loop(
+        root,
+        element,
+        spawner,
+        NestedObjectModel {
+            inner: object_model,
+            nested_root,
+        },
+    )
+    .await
+}
@@ -0,0 +1,342 @@
+use std::{
+    any::{Any, TypeId},
+    collections::HashMap,
+    fs,
+    hash::Hash,
+    ops::Deref,
+    path::PathBuf,
+    sync::{Arc, Mutex, Weak},
+};
+
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::{
+    context::ContextMap, state::State, use_context, use_ref_with_default, use_state, Element,
+};
+
+/// A storage backend for [use_persistent_state].
+/// Values are passed in their serialized (JSON) form.
+pub trait StateStorage: Send + Sync {
+    fn get(&self, key: &str) -> Option<String>;
+    fn set(&self, key: &str, value: String);
+}
+
+/// Keeps persistent state in memory.
+/// Useful for tests and server-side rendering.
+#[derive(Default)]
+pub struct MemoryStorage(Mutex<HashMap<String, String>>);
+
+impl StateStorage for MemoryStorage {
+    fn get(&self, key: &str) -> Option<String> {
+        self.0
+            .lock()
+            .expect("Failed to lock memory storage")
+            .get(key)
+            .cloned()
+    }
+
+    fn set(&self, key: &str, value: String) {
+        self.0
+            .lock()
+            .expect("Failed to lock memory storage")
+            .insert(key.to_string(), value);
+    }
+}
+
+/// Keeps persistent state in a JSON file, e.G. for terminal applications.
+pub struct FileStorage {
+    path: PathBuf,
+    lock: Mutex<()>,
+}
+
+impl FileStorage {
+    pub fn new<P>(path: P) -> Self
+    where
+        P: Into<PathBuf>,
+    {
+        Self {
+            path: path.into(),
+            lock: Mutex::new(()),
+        }
+    }
+
+    fn read(&self) -> HashMap<String, String> {
+        fs::read_to_string(&self.path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+}
+
+impl StateStorage for FileStorage {
+    fn get(&self, key: &str) -> Option<String> {
+        let _lock = self.lock.lock().expect("Failed to lock file storage");
+        self.read().remove(key)
+    }
+
+    fn set(&self, key: &str, value: String) {
+        let _lock = self.lock.lock().expect("Failed to lock file storage");
+        let mut values = self.read();
+        values.insert(key.to_string(), value);
+        if let Ok(content) = serde_json::to_string(&values) {
+            let _ = fs::write(&self.path, content);
+        }
+    }
+}
+
+type Subscriber = dyn Fn(&str) + Send + Sync;
+
+/// The storage used by [use_persistent_state].
+/// Provide it via context to choose a backend:
+/// ```ignore
+/// Provider::new(PersistentStorage::new(FileStorage::new("settings.json"))).children(vec![rsx!(<App />)])
+/// ```
+/// Without a provider, each tree keeps its state in its own [MemoryStorage],
+/// so it is not shared between e.G. server-side rendered requests.
+#[derive(Clone)]
+pub struct PersistentStorage {
+    backend: Arc<dyn StateStorage>,
+    subscribers: Arc<Mutex<HashMap<String, Vec<Weak<Subscriber>>>>>,
+}
+
+impl PersistentStorage {
+    pub fn new<S>(backend: S) -> Self
+    where
+        S: StateStorage + 'static,
+    {
+        Self {
+            backend: Arc::new(backend),
+            subscribers: Arc::default(),
+        }
+    }
+
+    pub(crate) fn read<T>(&self, key: &str) -> Option<T>
+    where
+        T: DeserializeOwned,
+    {
+        self.backend
+            .get(key)
+            .and_then(|value| serde_json::from_str(&value).ok())
+    }
+
+    fn write<T>(&self, key: &str, value: &T, origin: &Weak<Subscriber>)
+    where
+        T: Serialize,
+    {
+        let value = match serde_json::to_string(value) {
+            Ok(value) => value,
+            Err(_) => return,
+        };
+        self.backend.set(key, value.clone());
+
+        let subscribers = self
+            .subscribers
+            .lock()
+            .expect("Failed to lock subscribers")
+            .get_mut(key)
+            .map(|subscribers| {
+                subscribers.retain(|subscriber| subscriber.strong_count() > 0);
+                subscribers
+                    .iter()
+                    .filter(|subscriber| !Weak::ptr_eq(subscriber, origin))
+                    .filter_map(Weak::upgrade)
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+
+        for subscriber in subscribers {
+            subscriber(&value);
+        }
+    }
+
+    fn subscribe(&self, key: &str, subscriber: &Arc<Subscriber>) {
+        self.subscribers
+            .lock()
+            .expect("Failed to lock subscribers")
+            .entry(key.to_string())
+            .or_default()
+            .push(Arc::downgrade(subscriber));
+    }
+}
+
+impl Default for PersistentStorage {
+    fn default() -> Self {
+        PersistentStorage::new(MemoryStorage::default())
+    }
+}
+
+/// Adds the storage of a tree to its root context unless a [PersistentStorage] is provided already.
+pub(crate) fn with_tree_storage(
+    context: ContextMap,
+    storage: &Arc<dyn Any + Send + Sync>,
+) -> ContextMap {
+    if context.contains_key(&TypeId::of::<PersistentStorage>()) {
+        return context;
+    }
+    let mut context = context.as_ref().clone();
+    context.insert(TypeId::of::<PersistentStorage>(), storage.clone());
+    Arc::new(context)
+}
+
+/// The state object returned from [use_persistent_state].
+/// It works like the object returned from [crate::use_state] but
+/// updates are written to the storage backend and shared
+/// with all components using the same key.
+pub struct PersistentState<T> {
+    state: State<T>,
+    key: Arc<str>,
+    storage: PersistentStorage,
+    subscriber: Weak<Subscriber>,
+}
+
+impl<T> Clone for PersistentState<T> {
+    fn clone(&self) -> Self {
+        Self {
+            state: self.state.clone(),
+            key: self.key.clone(),
+            storage: self.storage.clone(),
+            subscriber: self.subscriber.clone(),
+        }
+    }
+}
+
+impl<T> Deref for PersistentState<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.state
+    }
+}
+
+impl<T> Hash for PersistentState<T> {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.state.hash(state);
+    }
+}
+
+impl<N, E, T> From<PersistentState<T>> for Element<N, E>
+where
+    N: From<String>,
+    T: ToString,
+{
+    fn from(value: PersistentState<T>) -> Self {
+        value.state.into()
+    }
+}
+
+impl<T> PersistentState<T>
+where
+    T: Serialize + DeserializeOwned + Send + Sync + 'static,
+{
+    pub fn update<C>(&self, callback: C)
+    where
+        C: FnOnce(Arc<T>) -> T + Send + Sync + 'static,
+    {
+        let key = self.key.clone();
+        let storage = self.storage.clone();
+        let subscriber = self.subscriber.clone();
+        self.state.update(move |value| {
+            let value = callback(value);
+            storage.write(&key, &value, &subscriber);
+            value
+        });
+    }
+}
+
+/// Like [crate::use_state] but the value is persisted using the [StateStorage]
+/// provided via [PersistentStorage] and restored on the next mount.
+/// All components using the same key are kept in sync.
+/// The key must not change between renders.
+/// ```ignore
+/// let theme = use_persistent_state("theme", || Theme::Light);
+///
+/// theme.update(|_| Theme::Dark);
+/// ```
+pub fn use_persistent_state<T, D>(key: &str, default: D) -> PersistentState<T>
+where
+    T: Serialize + DeserializeOwned + Send + Sync + 'static,
+    D: FnOnce() -> T,
+{
+    let storage = use_context::<PersistentStorage>();
+    let state = use_state(|| storage.read(key).unwrap_or_else(default));
+
+    let subscriber = use_ref_with_default(|| {
+        let state = state.clone();
+        let subscriber: Arc<Subscriber> = Arc::new(move |value: &str| {
+            if let Ok(value) = serde_json::from_str::<T>(value) {
+                state.update(move |_| value);
+            }
+        });
+        storage.subscribe(key, &subscriber);
+        subscriber
+    });
+
+    PersistentState {
+        state,
+        key: key.into(),
+        storage: storage.as_ref().clone(),
+        subscriber: Arc::downgrade(subscriber.as_ref()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn file_storage() {
+        let path =
+            std::env::temp_dir().join(format!("bloom-file-storage-{}.json", std::process::id()));
+        let storage = FileStorage::new(&path);
+
+        assert_eq!(storage.get("foo"), None);
+
+        storage.set("foo", "1".to_string());
+        storage.set("bar", "\"baz\"".to_string());
+
+        let storage = FileStorage::new(&path);
+        assert_eq!(storage.get("foo"), Some("1".to_string()));
+        assert_eq!(storage.get("bar"), Some("\"baz\"".to_string()));
+
+        let _ = fs::remove_file(path);
+    }
+
+    #[test]
+    fn read_from_storage() {
+        let storage = PersistentStorage::new(MemoryStorage::default());
+        storage.backend.set("count", "42".to_string());
+
+        assert_eq!(storage.read::<i32>("count"), Some(42));
+        assert_eq!(storage.read::<String>("count"), None);
+        assert_eq!(storage.read::<i32>("missing"), None);
+    }
+
+    #[test]
+    fn keep_storage_per_tree() {
+        let tree_storage: Arc<dyn Any + Send + Sync> = Arc::new(PersistentStorage::default());
+        let context = with_tree_storage(Arc::default(), &tree_storage);
+        assert!(Arc::ptr_eq(
+            &context[&TypeId::of::<PersistentStorage>()],
+            &tree_storage
+        ));
+
+        let other_storage: Arc<dyn Any + Send + Sync> = Arc::new(PersistentStorage::default());
+        let other_context = with_tree_storage(Arc::default(), &other_storage);
+        other_storage
+            .downcast_ref::<PersistentStorage>()
+            .unwrap()
+            .backend
+            .set("count", "1".to_string());
+        assert_eq!(
+            tree_storage
+                .downcast_ref::<PersistentStorage>()
+                .unwrap()
+                .read::<i32>("count"),
+            None
+        );
+
+        // A provided storage is kept, e.G. in nested trees
+        let nested_context = with_tree_storage(other_context.clone(), &tree_storage);
+        assert!(Arc::ptr_eq(&nested_context, &other_context));
+    }
+}
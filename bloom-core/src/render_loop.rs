@@ -9,9 +9,11 @@ use futures_util::{
 
 use crate::{
     component::{AnyComponent, ComponentDiff},
-    context::ContextMap,
+    context::{context_eq, ContextMap},
     hook::Hook,
+    persistent_state::{with_tree_storage, PersistentStorage},
     render_queue::{RenderContext, RenderQueue, RenderQueueItem},
+    spawn::{share_spawner, SharedSpawner},
     state::StateUpdate,
     suspense::{run_or_suspend, RunOrSuspendResult},
     Element,
@@ -28,13 +30,18 @@ where
     render_result: Option<Pin<Box<dyn Future<Output = (Result<Element<N, E>, E>, Hook)> + Send>>>,
     child: Option<Box<TreeNode<N, E>>>,
     refs: HashMap<u16, Arc<dyn Any + Send + Sync + 'static>>,
+    context: ContextMap,
+    path: Arc<str>,
 }
 
 impl<N, E> TreeComponent<N, E>
 where
     N: From<String>,
 {
-    fn new(component: Arc<dyn AnyComponent<Node = N, Error = E> + Send + Sync>) -> Self {
+    fn new(
+        component: Arc<dyn AnyComponent<Node = N, Error = E> + Send + Sync>,
+        path: Arc<str>,
+    ) -> Self {
         let (update_sender, update_receiver) = unbounded::<StateUpdate>();
         Self {
             component,
@@ -44,6 +51,8 @@ where
             child: None,
             render_result: None,
             refs: HashMap::new(),
+            context: Arc::default(),
+            path,
         }
     }
 }
@@ -63,24 +72,43 @@ where
     N: From<String>,
 {
     fn from(element: Element<N, E>) -> Self {
+        Self::from_output(element, "")
+    }
+
+    /// Builds the tree for the output of the component at the given path.
+    /// Components are numbered in document order, just like in render_stream,
+    /// so that component paths match between server-side rendering and hydration.
+    fn from_output(element: Element<N, E>, path: &str) -> Self {
+        Self::from_element(element, path, &mut 0)
+    }
+
+    fn from_element(element: Element<N, E>, path: &str, index: &mut u16) -> Self {
         match element {
-            Element::Component(component) => TreeNode::Component(TreeComponent::new(component)),
+            Element::Component(component) => {
+                let component_path = format!("{}/{}", path, index);
+                *index += 1;
+                TreeNode::Component(TreeComponent::new(component, component_path.into()))
+            }
             Element::Node(node, children) => TreeNode::Node(
                 Arc::new(node),
                 children
                     .into_iter()
-                    .map(|child| TreeNode::from(child))
+                    .map(|child| TreeNode::from_element(child, path, index))
                     .collect(),
             ),
             Element::Fragment(children) => TreeNode::Fragment(
                 children
                     .into_iter()
-                    .map(|child| TreeNode::from(child))
+                    .map(|child| TreeNode::from_element(child, path, index))
+                    .collect(),
+            ),
+            Element::Provider(value, children) => TreeNode::Provider(
+                value,
+                children
+                    .into_iter()
+                    .map(|child| TreeNode::from_element(child, path, index))
                     .collect(),
             ),
-            Element::Provider(value, children) => {
-                TreeNode::Provider(value, children.into_iter().map(TreeNode::from).collect())
-            }
         }
     }
 
@@ -113,6 +141,8 @@ where
 
 pub trait ObjectModel {
     type Node;
+    /// The error type of the host environment, e.G. a failed DOM operation.
+    type Error;
     fn start(&mut self) -> impl Future<Output = ()> + Send {
         // Do nothing by default
         future::ready(())
@@ -122,9 +152,14 @@ pub trait ObjectModel {
         node: &Arc<Self::Node>,
         parent: &Arc<Self::Node>,
         sibling: &Option<Arc<Self::Node>>,
-    );
-    fn remove(&mut self, node: &Arc<Self::Node>, parent: &Arc<Self::Node>);
-    fn update(&mut self, node: &Arc<Self::Node>, next: &Arc<Self::Node>);
+    ) -> Result<(), Self::Error>;
+    fn remove(
+        &mut self,
+        node: &Arc<Self::Node>,
+        parent: &Arc<Self::Node>,
+    ) -> Result<(), Self::Error>;
+    fn update(&mut self, node: &Arc<Self::Node>, next: &Arc<Self::Node>)
+        -> Result<(), Self::Error>;
     fn finalize(&mut self) -> impl Future<Output = ()> + Send {
         // Do nothing by default
         future::ready(())
@@ -135,9 +170,15 @@ pub trait ObjectModel {
     fn get_context(&mut self) -> ContextMap {
         Arc::default()
     }
-    fn set_context(&mut self, _ctx: ContextMap) {
-        // do nothing by default
-    }
+}
+
+/// The error returned from [render_loop].
+/// Errors can either originate from a component's render function
+/// or from the object model, i.e. the host environment.
+#[derive(Debug, PartialEq)]
+pub enum RenderError<E, H> {
+    Component(E),
+    Host(H),
 }
 
 /// render_loop can be used to implement interactive renderers on top of bloom-core.
@@ -168,27 +209,29 @@ pub trait ObjectModel {
 /// arbitrary task runners such as tokio or async-std.
 ///
 ///  The object model is the interface to the host environment.
+///  Errors from the object model abort the render loop and are returned as [RenderError::Host].
 pub async fn render_loop<N, E, S, P>(
     root: Arc<N>,
     element: Element<N, E>,
     spawner: S,
     mut object_model: P,
-) -> Result<(), E>
+) -> Result<(), RenderError<E, P::Error>>
 where
     N: From<String> + Send + 'static,
     E: Send + 'static,
-    S: Spawn,
+    S: Spawn + Send + 'static,
     P: ObjectModel<Node = N>,
 {
+    let spawner = share_spawner(spawner);
     let mut tree_root = TreeNode::from(element);
+    let storage: Arc<dyn Any + Send + Sync> = Arc::new(PersistentStorage::default());
 
     let (signal_sender, signal_receiver) = bounded::<()>(1);
 
     object_model.subscribe(signal_sender.clone());
 
-    signal_sender
-        .try_send(())
-        .expect("Failed to send message to trigger initial render");
+    // The object model might already have closed the signal, in which case the loop ends right away
+    let _ = signal_sender.try_send(());
 
     while let Ok(_) = signal_receiver.recv().await {
         println!("start render cycle");
@@ -197,7 +240,11 @@ where
             let mut render_queue = RenderQueue::new();
             render_queue.reload(
                 &mut tree_root,
-                RenderContext::new(root.clone(), None, object_model.get_context()),
+                RenderContext::new(
+                    root.clone(),
+                    None,
+                    with_tree_storage(object_model.get_context(), &storage),
+                ),
             );
 
             while let Some(item) = render_queue.next() {
@@ -210,9 +257,12 @@ where
                             &signal_sender,
                             ctx,
                             &spawner,
-                        )?,
+                        )
+                        .map_err(RenderError::Component)?,
                         TreeNode::Node(node, children) => {
-                            object_model.create(node, &ctx.parent, &ctx.sibling);
+                            object_model
+                                .create(node, &ctx.parent, &ctx.sibling)
+                                .map_err(RenderError::Host)?;
                             for child in children.iter_mut().rev() {
                                 render_queue.create(child, ctx.with_parent(node.clone()));
                             }
@@ -238,7 +288,9 @@ where
                     RenderQueueItem::Reload { current, ctx } => match unsafe { &mut *current } {
                         TreeNode::Component(component) => {
                             dbg!("reload component");
-                            if component.updates.is_empty() {
+                            if component.updates.is_empty()
+                                && context_eq(&component.context, &ctx.context)
+                            {
                                 if let Some(render_result) = component.render_result.take() {
                                     match run_or_suspend(render_result) {
                                         RunOrSuspendResult::Suspend(render_result) => {
@@ -251,10 +303,18 @@ where
                                             render_queue
                                                 .queue_effects(&component.component, hook.effects);
                                             component.refs = hook.refs;
+                                            component.state = hook.state;
                                             if let Some(ref mut child) = component.child {
-                                                render_queue.update(child.as_mut(), result?, ctx);
+                                                render_queue.update(
+                                                    child.as_mut(),
+                                                    result.map_err(RenderError::Component)?,
+                                                    ctx,
+                                                );
                                             } else {
-                                                let mut child = Box::new(TreeNode::from(result?));
+                                                let mut child = Box::new(TreeNode::from_output(
+                                                    result.map_err(RenderError::Component)?,
+                                                    &component.path,
+                                                ));
                                                 render_queue.create(child.as_mut(), ctx);
                                                 component.child = Some(child);
                                             }
@@ -269,7 +329,8 @@ where
                                         &signal_sender,
                                         ctx,
                                         &spawner,
-                                    )?
+                                    )
+                                    .map_err(RenderError::Component)?
                                 }
                             } else {
                                 render_component(
@@ -278,7 +339,8 @@ where
                                     &signal_sender,
                                     ctx,
                                     &spawner,
-                                )?
+                                )
+                                .map_err(RenderError::Component)?
                             }
                         }
                         TreeNode::Node(node, children) => {
@@ -334,7 +396,8 @@ where
                                         &signal_sender,
                                         ctx,
                                         &spawner,
-                                    )?;
+                                    )
+                                    .map_err(RenderError::Component)?;
                                 }
                                 ComponentDiff::NewType => {
                                     render_queue.queue_cleanups(&current_component.component);
@@ -351,7 +414,9 @@ where
                                 Element::Node(next, next_children),
                             ) => {
                                 let next = Arc::new(next);
-                                object_model.update(current, &next);
+                                object_model
+                                    .update(current, &next)
+                                    .map_err(RenderError::Host)?;
                                 *current = next.clone();
                                 update_children(
                                     current_children,
@@ -394,7 +459,9 @@ where
                             }
                         }
                         TreeNode::Node(node, children) => {
-                            object_model.remove(&node, &parent);
+                            object_model
+                                .remove(&node, &parent)
+                                .map_err(RenderError::Host)?;
                             for child in children {
                                 render_queue.remove(child, Arc::clone(&node));
                             }
@@ -453,17 +520,16 @@ fn update_children<N, E>(
     }
 }
 
-fn render_component<N, E, S>(
+fn render_component<N, E>(
     tree_component: &mut TreeComponent<N, E>,
     render_queue: &mut RenderQueue<N, E, TreeNode<N, E>>,
     signal_sender: &Sender<()>,
     ctx: RenderContext<N>,
-    spawner: &S,
+    spawner: &SharedSpawner,
 ) -> Result<(), E>
 where
     N: From<String> + Send + 'static,
     E: Send + 'static,
-    S: Spawn,
 {
     dbg!("render component");
     while let Ok(state_update) = tree_component.updates.try_recv() {
@@ -477,7 +543,10 @@ where
         tree_component.state.clone(),
         tree_component.refs.clone(),
         ctx.context.clone(),
+        tree_component.path.clone(),
+        spawner.clone(),
     );
+    tree_component.context = ctx.context.clone();
     let result = run_or_suspend(Box::pin(async_context::provide_async_context(
         hook,
         component.render(),
@@ -489,7 +558,7 @@ where
             match tree_component.child {
                 Some(ref mut node) => render_queue.update(node.as_mut(), element?, ctx.clone()),
                 None => {
-                    let tree_node = TreeNode::from(element?);
+                    let tree_node = TreeNode::from_output(element?, &tree_component.path);
                     let mut child = Box::new(tree_node);
                     render_queue.create(child.as_mut(), ctx.clone());
                     tree_component.child = Some(child);
@@ -497,6 +566,7 @@ where
             }
             render_queue.queue_effects(&tree_component.component, hook.effects);
             tree_component.refs = hook.refs;
+            tree_component.state = hook.state;
         }
         RunOrSuspendResult::Suspend(render_future) => {
             let signal_sender = signal_sender.clone();
@@ -539,7 +609,12 @@ mod tests {
     use async_trait::async_trait;
     use futures_util::{task::Spawn, Future, FutureExt};
 
-    use crate::{use_effect, use_state, Component, Element, ObjectModel};
+    use crate::{
+        render_nested_loop, state::State, use_context, use_effect, use_nested_root,
+        use_persistent_state, use_state, Component, Element, MemoryStorage, NestedRoot,
+        ObjectModel, PersistentStorage, Provider, RenderError,
+    };
+    use crate::{use_resource, use_serialized_state, Resource, SerializedState};
 
     struct InnerMockObjectModel {
         created: VecDeque<Arc<MockNode>>,
@@ -614,30 +689,34 @@ mod tests {
 
     impl ObjectModel for MockObjectModel {
         type Node = MockNode;
+        type Error = ();
         fn create(
             &mut self,
             node: &std::sync::Arc<Self::Node>,
             _parent: &std::sync::Arc<Self::Node>,
             _sibling: &Option<std::sync::Arc<Self::Node>>,
-        ) {
+        ) -> Result<(), Self::Error> {
             println!("create {:?}", node);
             self.0.lock().unwrap().created.push_back(node.clone());
+            Ok(())
         }
 
         fn update(
             &mut self,
             _node: &std::sync::Arc<Self::Node>,
             next: &std::sync::Arc<Self::Node>,
-        ) {
+        ) -> Result<(), Self::Error> {
             self.0.lock().unwrap().updated.push_back(next.clone());
+            Ok(())
         }
 
         fn remove(
             &mut self,
             node: &std::sync::Arc<Self::Node>,
             _parent: &std::sync::Arc<Self::Node>,
-        ) {
+        ) -> Result<(), Self::Error> {
             self.0.lock().unwrap().removed.push_back(node.clone());
+            Ok(())
         }
 
         async fn start(&mut self) {
@@ -914,4 +993,426 @@ mod tests {
 
         handle.abort();
     }
+
+    #[tokio::test]
+    async fn host_error() {
+        struct FailingObjectModel;
+
+        impl ObjectModel for FailingObjectModel {
+            type Node = MockNode;
+            type Error = String;
+
+            fn create(
+                &mut self,
+                _node: &std::sync::Arc<Self::Node>,
+                _parent: &std::sync::Arc<Self::Node>,
+                _sibling: &Option<std::sync::Arc<Self::Node>>,
+            ) -> Result<(), Self::Error> {
+                Err("Failed to create node".to_string())
+            }
+
+            fn update(
+                &mut self,
+                _node: &std::sync::Arc<Self::Node>,
+                _next: &std::sync::Arc<Self::Node>,
+            ) -> Result<(), Self::Error> {
+                Ok(())
+            }
+
+            fn remove(
+                &mut self,
+                _node: &std::sync::Arc<Self::Node>,
+                _parent: &std::sync::Arc<Self::Node>,
+            ) -> Result<(), Self::Error> {
+                Ok(())
+            }
+        }
+
+        #[derive(PartialEq)]
+        struct MockComponent;
+
+        #[async_trait]
+        impl Component for MockComponent {
+            type Error = ();
+            type Node = MockNode;
+            async fn render(
+                self: Arc<Self>,
+            ) -> Result<Element<Self::Node, Self::Error>, Self::Error> {
+                Ok(Element::Node(MockNode(1), Vec::new()))
+            }
+        }
+
+        let root = Arc::new(MockNode(0));
+        let element = Element::Component(Arc::new(MockComponent));
+        let result = super::render_loop(root, element, TokioSpawner, FailingObjectModel).await;
+
+        assert_eq!(
+            result,
+            Err(RenderError::Host("Failed to create node".to_string()))
+        );
+    }
+
+    #[tokio::test]
+    async fn nested_root() {
+        #[derive(Default, Clone)]
+        struct Value(i32);
+
+        struct Parent(Sender<State<i32>>, Sender<NestedRoot>);
+
+        impl PartialEq for Parent {
+            fn eq(&self, _: &Self) -> bool {
+                true
+            }
+        }
+
+        #[async_trait]
+        impl Component for Parent {
+            type Error = ();
+            type Node = MockNode;
+
+            async fn render(
+                self: Arc<Self>,
+            ) -> Result<Element<Self::Node, Self::Error>, Self::Error> {
+                let value = use_state(|| 1i32);
+                let _ = self.0.try_send(value.clone());
+                Ok(Provider::new(Value(*value))
+                    .children(vec![Element::Component(Arc::new(Host(self.1.clone())))]))
+            }
+        }
+
+        struct Host(Sender<NestedRoot>);
+
+        impl PartialEq for Host {
+            fn eq(&self, _: &Self) -> bool {
+                true
+            }
+        }
+
+        #[async_trait]
+        impl Component for Host {
+            type Error = ();
+            type Node = MockNode;
+
+            async fn render(
+                self: Arc<Self>,
+            ) -> Result<Element<Self::Node, Self::Error>, Self::Error> {
+                let _ = self.0.try_send(use_nested_root());
+                Ok(Element::Node(MockNode(0), Vec::new()))
+            }
+        }
+
+        #[derive(PartialEq)]
+        struct Consumer;
+
+        #[async_trait]
+        impl Component for Consumer {
+            type Error = ();
+            type Node = MockNode;
+
+            async fn render(
+                self: Arc<Self>,
+            ) -> Result<Element<Self::Node, Self::Error>, Self::Error> {
+                let value = use_context::<Value>();
+                Ok(Element::Node(MockNode(value.0), Vec::new()))
+            }
+        }
+
+        let (state_sender, state_receiver) = async_channel::bounded(1);
+        let (nested_root_sender, nested_root_receiver) = async_channel::bounded(1);
+
+        let parent_object_model = InnerMockObjectModel::new();
+        let object_model = MockObjectModel(parent_object_model.clone());
+        let parent_handle = tokio::spawn(async move {
+            let root = Arc::new(MockNode(0));
+            let element = Element::Component(Arc::new(Parent(state_sender, nested_root_sender)));
+            super::render_loop(root, element, TokioSpawner, object_model)
+                .await
+                .unwrap();
+        });
+
+        let render_cycle = parent_object_model.lock().unwrap().render_cycle();
+        render_cycle.await;
+        let state = state_receiver.recv().await.unwrap();
+        let nested_root = nested_root_receiver.recv().await.unwrap();
+
+        let child_object_model = InnerMockObjectModel::new();
+        let object_model = MockObjectModel(child_object_model.clone());
+        let child_handle = tokio::spawn(async move {
+            let root = Arc::new(MockNode(0));
+            let element = Element::Component(Arc::new(Consumer));
+            render_nested_loop(nested_root, root, element, TokioSpawner, object_model).await
+        });
+
+        let render_cycle = child_object_model.lock().unwrap().render_cycle();
+        render_cycle.await;
+        child_object_model
+            .lock()
+            .unwrap()
+            .assert_created(MockNode(1));
+
+        state.update(|_| 2);
+        let render_cycle = parent_object_model.lock().unwrap().render_cycle();
+        render_cycle.await;
+        let render_cycle = child_object_model.lock().unwrap().render_cycle();
+        render_cycle.await;
+        child_object_model
+            .lock()
+            .unwrap()
+            .assert_updated(MockNode(2));
+
+        parent_handle.abort();
+        assert_eq!(child_handle.await.unwrap(), Ok(()));
+    }
+
+    #[tokio::test]
+    async fn persistent_state() {
+        #[derive(PartialEq)]
+        struct Writer;
+
+        #[async_trait]
+        impl Component for Writer {
+            type Error = ();
+            type Node = MockNode;
+
+            async fn render(
+                self: Arc<Self>,
+            ) -> Result<Element<Self::Node, Self::Error>, Self::Error> {
+                let count = use_persistent_state("count", || 0i32);
+                if *count == 0 {
+                    let count = count.clone();
+                    tokio::spawn(async move { count.update(|count| *count + 1) });
+                }
+                Ok(Element::Node(MockNode(*count), Vec::new()))
+            }
+        }
+
+        #[derive(PartialEq)]
+        struct Reader;
+
+        #[async_trait]
+        impl Component for Reader {
+            type Error = ();
+            type Node = MockNode;
+
+            async fn render(
+                self: Arc<Self>,
+            ) -> Result<Element<Self::Node, Self::Error>, Self::Error> {
+                let count = use_persistent_state("count", || 0i32);
+                Ok(Element::Node(MockNode(*count + 10), Vec::new()))
+            }
+        }
+
+        let storage = PersistentStorage::new(MemoryStorage::default());
+        let inner_object_model = InnerMockObjectModel::new();
+        let object_model = MockObjectModel(inner_object_model.clone());
+        let provided_storage = storage.clone();
+        let handle = tokio::spawn(async move {
+            let root = Arc::new(MockNode(0));
+            let element = Provider::new(provided_storage).children(vec![
+                Element::Component(Arc::new(Writer)),
+                Element::Component(Arc::new(Reader)),
+            ]);
+            super::render_loop(root, element, TokioSpawner, object_model)
+                .await
+                .unwrap();
+        });
+
+        let render_cycle = inner_object_model.lock().unwrap().render_cycle();
+        render_cycle.await;
+        {
+            let mut lock = inner_object_model.lock().unwrap();
+            lock.assert_created(MockNode(0));
+            lock.assert_created(MockNode(10));
+        }
+
+        let render_cycle = inner_object_model.lock().unwrap().render_cycle();
+        render_cycle.await;
+        {
+            let mut lock = inner_object_model.lock().unwrap();
+            lock.assert_updated(MockNode(1));
+            lock.assert_updated(MockNode(11));
+        }
+
+        assert_eq!(storage.read::<i32>("count"), Some(1));
+
+        handle.abort();
+    }
+
+    #[tokio::test]
+    async fn serialized_state() {
+        #[derive(PartialEq)]
+        struct Counter;
+
+        #[async_trait]
+        impl Component for Counter {
+            type Error = ();
+            type Node = MockNode;
+
+            async fn render(
+                self: Arc<Self>,
+            ) -> Result<Element<Self::Node, Self::Error>, Self::Error> {
+                let count = use_serialized_state(|| 0i32);
+                Ok(Element::Node(MockNode(*count), Vec::new()))
+            }
+        }
+
+        #[derive(PartialEq)]
+        struct Parent;
+
+        #[async_trait]
+        impl Component for Parent {
+            type Error = ();
+            type Node = MockNode;
+
+            async fn render(
+                self: Arc<Self>,
+            ) -> Result<Element<Self::Node, Self::Error>, Self::Error> {
+                Ok(Element::Fragment(vec![
+                    Element::Component(Arc::new(Counter)),
+                    Element::Component(Arc::new(Counter)),
+                ]))
+            }
+        }
+
+        let serialized_state = SerializedState::restore(r#"{"/0/1:0":7}"#);
+        let inner_object_model = InnerMockObjectModel::new();
+        let object_model = MockObjectModel(inner_object_model.clone());
+        let provided_state = serialized_state.clone();
+        let handle = tokio::spawn(async move {
+            let root = Arc::new(MockNode(0));
+            let element =
+                Provider::new(provided_state).children(vec![Element::Component(Arc::new(Parent))]);
+            super::render_loop(root, element, TokioSpawner, object_model)
+                .await
+                .unwrap();
+        });
+
+        let render_cycle = inner_object_model.lock().unwrap().render_cycle();
+        render_cycle.await;
+        {
+            let mut lock = inner_object_model.lock().unwrap();
+            lock.assert_created(MockNode(0));
+            lock.assert_created(MockNode(7));
+        }
+
+        assert!(serialized_state.is_empty());
+
+        handle.abort();
+    }
+
+    #[tokio::test]
+    async fn resource() {
+        struct Loader(Receiver<i32>);
+
+        impl PartialEq for Loader {
+            fn eq(&self, _other: &Self) -> bool {
+                true
+            }
+        }
+
+        #[async_trait]
+        impl Component for Loader {
+            type Error = ();
+            type Node = MockNode;
+
+            async fn render(
+                self: Arc<Self>,
+            ) -> Result<Element<Self::Node, Self::Error>, Self::Error> {
+                let receiver = self.0.clone();
+                let value = use_resource((), async move { receiver.recv().await }).await;
+                Ok(Element::Node(
+                    match value.as_ref() {
+                        Resource::Loading => MockNode(0),
+                        Resource::Ready(value) => MockNode(*value),
+                        Resource::Failed(_) => MockNode(-1),
+                    },
+                    Vec::new(),
+                ))
+            }
+        }
+
+        let (sender, receiver) = async_channel::bounded(1);
+        let inner_object_model = InnerMockObjectModel::new();
+        let object_model = MockObjectModel(inner_object_model.clone());
+        let handle = tokio::spawn(async move {
+            let root = Arc::new(MockNode(0));
+            let element = Element::Component(Arc::new(Loader(receiver)));
+            super::render_loop(root, element, TokioSpawner, object_model)
+                .await
+                .unwrap();
+        });
+
+        let render_cycle = inner_object_model.lock().unwrap().render_cycle();
+        render_cycle.await;
+        inner_object_model
+            .lock()
+            .unwrap()
+            .assert_created(MockNode(0));
+
+        sender.send(42).await.unwrap();
+
+        let render_cycle = inner_object_model.lock().unwrap().render_cycle();
+        render_cycle.await;
+        inner_object_model
+            .lock()
+            .unwrap()
+            .assert_updated(MockNode(42));
+
+        handle.abort();
+    }
+
+    #[tokio::test]
+    async fn restore_resource() {
+        #[derive(PartialEq)]
+        struct Loader;
+
+        #[async_trait]
+        impl Component for Loader {
+            type Error = ();
+            type Node = MockNode;
+
+            async fn render(
+                self: Arc<Self>,
+            ) -> Result<Element<Self::Node, Self::Error>, Self::Error> {
+                let value =
+                    use_resource((), futures_util::future::pending::<Result<i32, ()>>()).await;
+                let after = use_serialized_state(|| 0i32);
+                Ok(Element::Fragment(vec![
+                    Element::Node(
+                        match value.as_ref() {
+                            Resource::Ready(value) => MockNode(*value),
+                            _ => MockNode(0),
+                        },
+                        Vec::new(),
+                    ),
+                    Element::Node(MockNode(*after), Vec::new()),
+                ]))
+            }
+        }
+
+        let serialized_state = SerializedState::restore(r#"{"/0:0":42,"/0:2":7}"#);
+        let inner_object_model = InnerMockObjectModel::new();
+        let object_model = MockObjectModel(inner_object_model.clone());
+        let provided_state = serialized_state.clone();
+        let handle = tokio::spawn(async move {
+            let root = Arc::new(MockNode(0));
+            let element =
+                Provider::new(provided_state).children(vec![Element::Component(Arc::new(Loader))]);
+            super::render_loop(root, element, TokioSpawner, object_model)
+                .await
+                .unwrap();
+        });
+
+        let render_cycle = inner_object_model.lock().unwrap().render_cycle();
+        render_cycle.await;
+        {
+            let mut lock = inner_object_model.lock().unwrap();
+            lock.assert_created(MockNode(42));
+            lock.assert_created(MockNode(7));
+        }
+
+        assert!(serialized_state.is_empty());
+
+        handle.abort();
+    }
 }
@@ -58,7 +58,7 @@ impl<N> RenderContext<N> {
 
     pub(crate) fn with_context(&self, value: Arc<dyn Any + Send + Sync>) -> Self {
         let mut new_context = self.context.as_ref().clone();
-        new_context.insert(value.type_id(), value);
+        new_context.insert(value.as_ref().type_id(), value);
         Self {
             parent: self.parent.clone(),
             sibling: self.sibling.clone(),
@@ -72,7 +72,7 @@ impl<N> RenderContext<N> {
         value: Arc<dyn Any + Send + Sync>,
     ) -> Self {
         let mut new_context = self.context.as_ref().clone();
-        new_context.insert(value.type_id(), value);
+        new_context.insert(value.as_ref().type_id(), value);
         Self {
             parent: self.parent.clone(),
             sibling,
@@ -2,7 +2,10 @@ use std::{
     any::{Any, TypeId},
     collections::HashMap,
     pin::Pin,
-    sync::Arc,
+    sync::{
+        atomic::{AtomicU16, Ordering},
+        Arc,
+    },
     task::Poll,
 };
 
@@ -14,11 +17,33 @@ use futures_util::{
     Future, Stream, StreamExt,
 };
 
-use crate::{hook::Hook, Element};
+use crate::{
+    hook::Hook,
+    persistent_state::PersistentStorage,
+    serialized_state::record_serialized_state,
+    spawn::{share_spawner, SharedSpawner},
+    suspense::Suspense,
+    Element,
+};
 
 use pin_project::pin_project;
 
-type NodeStreamItem<N, E> = Result<(N, NodeStream<N, E>), E>;
+/// The items yielded from a [NodeStream].
+pub enum StreamItem<N, E> {
+    /// A node and the stream of its children.
+    Node(N, NodeStream<N, E>),
+    /// The output of a component or a fragment.
+    /// Renderers can mark its boundaries, e.G. to make hydration more reliable.
+    Fragment(NodeStream<N, E>),
+    /// A [Suspense] boundary.
+    /// Renderers can send the fallback while the children are still pending.
+    Suspense {
+        fallback: NodeStream<N, E>,
+        children: NodeStream<N, E>,
+    },
+}
+
+type NodeStreamItem<N, E> = Result<StreamItem<N, E>, E>;
 
 #[pin_project]
 pub struct NodeStream<N, E>(#[pin] Pin<Box<dyn Stream<Item = NodeStreamItem<N, E>> + Send>>);
@@ -56,20 +81,50 @@ where
 #[derive(Clone)]
 struct RenderContext {
     context: Arc<HashMap<TypeId, Arc<dyn Any + Send + Sync>>>,
+    path: Arc<str>,
+    component_index: Arc<AtomicU16>,
+    spawner: SharedSpawner,
 }
 
 impl RenderContext {
-    fn new() -> Self {
+    fn new(spawner: SharedSpawner) -> Self {
+        // Every stream gets its own storage for use_persistent_state
+        let storage: Arc<dyn Any + Send + Sync> = Arc::new(PersistentStorage::default());
         Self {
-            context: Arc::new(HashMap::new()),
+            context: Arc::new(HashMap::from([(
+                TypeId::of::<PersistentStorage>(),
+                storage,
+            )])),
+            path: "".into(),
+            component_index: Arc::default(),
+            spawner,
         }
     }
 
     fn with_context(&self, value: Arc<dyn Any + Send + Sync>) -> Self {
         let mut new_context = self.context.as_ref().clone();
-        new_context.insert(value.type_id(), value);
+        new_context.insert(value.as_ref().type_id(), value);
         Self {
             context: Arc::new(new_context),
+            path: self.path.clone(),
+            component_index: self.component_index.clone(),
+            spawner: self.spawner.clone(),
+        }
+    }
+
+    /// Components are numbered in document order within the output of their parent component.
+    /// This has to happen synchronously to be consistent with the render loop.
+    fn next_component_path(&self) -> Arc<str> {
+        let index = self.component_index.fetch_add(1, Ordering::Relaxed);
+        format!("{}/{}", self.path, index).into()
+    }
+
+    fn with_path(&self, path: Arc<str>) -> Self {
+        Self {
+            context: self.context.clone(),
+            path,
+            component_index: Arc::default(),
+            spawner: self.spawner.clone(),
         }
     }
 }
@@ -85,26 +140,50 @@ where
     S: Spawn + Clone + Send + 'static,
 {
     match element {
-        Element::Component(component) => Box::pin(async move {
-            match provide_async_context(Hook::from_context(ctx.context.clone()), component.render())
-                .await
-            {
-                (Ok(element), _) => render_element(element, spawner, ctx).await,
-                (Err(error), _) => NodeStream::ready(Err(error)),
+        Element::Component(component) => {
+            let ctx = ctx.with_path(ctx.next_component_path());
+            Box::pin(async move {
+                let (hook, updates) =
+                    Hook::from_context(ctx.context.clone(), ctx.path.clone(), ctx.spawner.clone());
+                match provide_async_context(hook, component.render()).await {
+                    (Ok(element), hook) => {
+                        record_serialized_state(hook, updates);
+                        NodeStream::ready(Ok(StreamItem::Fragment(
+                            render_element(element, spawner, ctx).await,
+                        )))
+                    }
+                    (Err(error), _) => NodeStream::ready(Err(error)),
+                }
+            })
+        }
+        Element::Node(node, children) => Box::pin(future::ready(NodeStream::ready(Ok(
+            StreamItem::Node(node, render_children(children, spawner, ctx)),
+        )))),
+        Element::Fragment(children) => Box::pin(future::ready(NodeStream::ready(Ok(
+            StreamItem::Fragment(render_children(children, spawner, ctx)),
+        )))),
+        Element::Provider(provider, children) => {
+            match provider.downcast_ref::<Suspense<N, E>>() {
+                Some(suspense) => {
+                    // The fallback is never rendered on the client, so it gets its own component paths
+                    let fallback = NodeStream::wrap(render_element(
+                        suspense.fallback(),
+                        spawner.clone(),
+                        ctx.with_path(format!("{}:fallback", ctx.path).into()),
+                    ));
+                    let children = render_children(children, spawner, ctx.with_context(provider));
+                    Box::pin(future::ready(NodeStream::ready(Ok(StreamItem::Suspense {
+                        fallback,
+                        children,
+                    }))))
+                }
+                None => Box::pin(future::ready(render_children(
+                    children,
+                    spawner,
+                    ctx.with_context(provider),
+                ))),
             }
-        }),
-        Element::Node(node, children) => Box::pin(future::ready(NodeStream::ready(Ok((
-            node,
-            render_children(children, spawner, ctx),
-        ))))),
-        Element::Fragment(children) => {
-            Box::pin(future::ready(render_children(children, spawner, ctx)))
         }
-        Element::Provider(provider, children) => Box::pin(future::ready(render_children(
-            children,
-            spawner,
-            ctx.with_context(provider),
-        ))),
     }
 }
 
@@ -140,6 +219,6 @@ where
     NodeStream::wrap(render_element(
         element,
         spawner.clone(),
-        RenderContext::new(),
+        RenderContext::new(share_spawner(spawner)),
     ))
 }
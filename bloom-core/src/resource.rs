@@ -0,0 +1,149 @@
+use std::{
+    any::Any,
+    hash::{DefaultHasher, Hash, Hasher},
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{Context, Poll},
+};
+
+use async_context::with_async_context;
+use futures_util::{
+    task::{waker, ArcWake},
+    Future,
+};
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+
+use crate::{
+    hook::Hook, serialized_state::use_serialized_state_with, state::State, use_ref_with_default,
+    use_state,
+};
+
+/// The value returned from [use_resource].
+#[derive(Debug, PartialEq)]
+pub enum Resource<T, E> {
+    Loading,
+    Ready(T),
+    Failed(E),
+}
+
+impl<T, E> From<Result<T, E>> for Resource<T, E> {
+    fn from(value: Result<T, E>) -> Self {
+        match value {
+            Ok(value) => Self::Ready(value),
+            Err(error) => Self::Failed(error),
+        }
+    }
+}
+
+/// Only resolved values are transferred to hydration,
+/// failed tasks are run again on the client.
+fn serialize_resource<T, E>(value: &Arc<dyn Any + Send + Sync>) -> Option<Value>
+where
+    T: Serialize + 'static,
+    E: 'static,
+{
+    match value.downcast_ref::<Arc<Resource<T, E>>>()?.as_ref() {
+        Resource::Ready(value) => serde_json::to_value(value).ok(),
+        _ => None,
+    }
+}
+
+fn restore_resource<T, E>(value: Value) -> Option<Arc<Resource<T, E>>>
+where
+    T: DeserializeOwned,
+{
+    serde_json::from_value(value)
+        .ok()
+        .map(|value| Arc::new(Resource::Ready(value)))
+}
+
+type ResourceFuture<T, E> = Pin<Box<dyn Future<Output = Result<T, E>> + Send>>;
+
+struct ResourceSlot<T, E> {
+    deps: Option<u64>,
+    future: Option<ResourceFuture<T, E>>,
+    value: Arc<Resource<T, E>>,
+}
+
+/// Wakes the component that polls a resource by triggering a re-render.
+struct ResourceWaker(State<u64>);
+
+impl ArcWake for ResourceWaker {
+    fn wake_by_ref(arc_self: &Arc<Self>) {
+        // The component might have been unmounted in the meantime
+        let _ = arc_self.0.try_update(|tick| *tick + 1);
+    }
+}
+
+/// use_resource runs an async task, e.G. to fetch some data.
+/// In the render loop, it returns [Resource::Loading] until the task is done
+/// and re-renders the component with the result once it is available.
+/// The task is restarted whenever the dependencies change.
+/// Under render_stream (server-side rendering), the task is awaited
+/// so the rendered output contains the result.
+/// The result is transferred to hydration like the value of `use_serialized_state`,
+/// so the task is not run again on the client.
+/// ```ignore
+/// let user = use_resource(self.user_id, load_user(self.user_id)).await;
+///
+/// match user.as_ref() {
+///     Resource::Loading => rsx!(<Spinner />),
+///     Resource::Ready(user) => rsx!(<div>{&user.name}</div>),
+///     Resource::Failed(_) => rsx!(<div>"Failed to load user"</div>),
+/// }
+/// ```
+pub async fn use_resource<D, F, T, E>(deps: D, future: F) -> Arc<Resource<T, E>>
+where
+    D: Hash,
+    F: Future<Output = Result<T, E>> + Send + 'static,
+    T: Serialize + DeserializeOwned + Send + Sync + 'static,
+    E: Send + Sync + 'static,
+{
+    let mut hasher = DefaultHasher::new();
+    deps.hash(&mut hasher);
+    let deps = hasher.finish();
+
+    // The same hooks are used on the server and the client so the hook indices match
+    let resolved = use_serialized_state_with(
+        || Arc::new(Resource::Loading),
+        restore_resource::<T, E>,
+        serialize_resource::<T, E>,
+    );
+    let tick = use_state(|| 0u64);
+    let slot = use_ref_with_default(|| {
+        let value: Arc<Resource<T, E>> = (*resolved).clone();
+        Mutex::new(ResourceSlot {
+            // A value from the server belongs to the current dependencies
+            deps: (!matches!(*value, Resource::Loading)).then_some(deps),
+            future: None,
+            value,
+        })
+    });
+
+    let streaming =
+        with_async_context(|hook: Option<&Hook>| hook.map(|hook| hook.streaming).unwrap_or(true));
+    if streaming {
+        let value = Arc::new(future.await.into());
+        let recorded = Arc::clone(&value);
+        let _ = resolved.try_update(move |_| recorded);
+        return value;
+    }
+
+    let mut slot = slot.lock().expect("Failed to lock resource");
+    if slot.deps != Some(deps) {
+        slot.deps = Some(deps);
+        slot.future = Some(Box::pin(future));
+        slot.value = Arc::new(Resource::Loading);
+    }
+
+    if let Some(future) = slot.future.as_mut() {
+        let waker = waker(Arc::new(ResourceWaker(tick)));
+        if let Poll::Ready(result) = future.as_mut().poll(&mut Context::from_waker(&waker)) {
+            slot.future = None;
+            slot.value = Arc::new(result.into());
+        }
+    }
+
+    slot.value.clone()
+}
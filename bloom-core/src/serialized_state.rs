@@ -0,0 +1,234 @@
+use std::{
+    any::{Any, TypeId},
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use async_channel::Receiver;
+use async_context::with_async_context_mut;
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+
+use crate::{
+    hook::Hook,
+    state::{next_state, State, StateUpdate},
+};
+
+pub(crate) type Serializer = fn(&Arc<dyn Any + Send + Sync>) -> Option<Value>;
+
+fn serialize<T>(value: &Arc<dyn Any + Send + Sync>) -> Option<Value>
+where
+    T: Serialize + 'static,
+{
+    value
+        .downcast_ref::<T>()
+        .and_then(|value| serde_json::to_value(value).ok())
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Mode {
+    Record,
+    Restore,
+}
+
+/// Transfers the values of [use_serialized_state] from server-side rendering to hydration.
+/// bloom-ssr provides a recording instance via context and embeds the recorded
+/// values in the rendered HTML.
+/// bloom-client restores them on hydration so the hooks start out with the
+/// values that were rendered on the server.
+///
+/// Values are identified by the position of their component in the tree
+/// and the position of the hook in the component.
+#[derive(Clone)]
+pub struct SerializedState {
+    values: Arc<Mutex<HashMap<String, Value>>>,
+    mode: Mode,
+}
+
+impl SerializedState {
+    /// Create an instance that records the values of all [use_serialized_state] hooks.
+    pub fn record() -> Self {
+        Self {
+            values: Arc::default(),
+            mode: Mode::Record,
+        }
+    }
+
+    /// Create an instance that seeds [use_serialized_state] hooks
+    /// from the output of [SerializedState::to_json].
+    /// Invalid input results in an empty state.
+    pub fn restore(json: &str) -> Self {
+        Self {
+            values: Arc::new(Mutex::new(serde_json::from_str(json).unwrap_or_default())),
+            mode: Mode::Restore,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values
+            .lock()
+            .expect("Failed to lock serialized state")
+            .is_empty()
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(&*self.values.lock().expect("Failed to lock serialized state"))
+            .expect("Failed to serialize state")
+    }
+
+    /// Drop all remaining values, e.G. once hydration is done.
+    pub fn clear(&self) {
+        self.values
+            .lock()
+            .expect("Failed to lock serialized state")
+            .clear();
+    }
+
+    #[cfg(test)]
+    fn take<T>(&self, key: &str) -> Option<T>
+    where
+        T: DeserializeOwned,
+    {
+        self.take_value(key)
+            .and_then(|value| serde_json::from_value(value).ok())
+    }
+
+    fn take_value(&self, key: &str) -> Option<Value> {
+        if self.mode != Mode::Restore {
+            return None;
+        }
+        self.values
+            .lock()
+            .expect("Failed to lock serialized state")
+            .remove(key)
+    }
+
+    fn insert(&self, key: String, value: Value) {
+        self.values
+            .lock()
+            .expect("Failed to lock serialized state")
+            .insert(key, value);
+    }
+}
+
+fn key(path: &str, index: u16) -> String {
+    format!("{}:{}", path, index)
+}
+
+fn get_serialized_state(hook: &Hook) -> Option<Arc<SerializedState>> {
+    hook.context
+        .get(&TypeId::of::<SerializedState>())
+        .and_then(|value| value.clone().downcast::<SerializedState>().ok())
+}
+
+/// Records the serializable state of a component after a single render pass.
+/// Updates issued during the render pass are applied first so that e.G.
+/// asynchronously loaded values end up in the recorded state.
+pub(crate) fn record_serialized_state(mut hook: Hook, updates: Receiver<StateUpdate>) {
+    if hook.serializers.is_empty() {
+        return;
+    }
+
+    let serialized_state = match get_serialized_state(&hook) {
+        Some(serialized_state) if serialized_state.mode == Mode::Record => serialized_state,
+        _ => return,
+    };
+
+    while let Ok(update) = updates.try_recv() {
+        update.apply(&mut hook.state);
+    }
+
+    for (index, serializer) in hook.serializers.iter() {
+        if let Some(value) = hook.state.get(index).and_then(serializer) {
+            serialized_state.insert(key(&hook.path, *index), value);
+        }
+    }
+}
+
+/// Works like [crate::use_state] but the value is transferred from
+/// server-side rendering to hydration.
+/// On the client, the default callback is only called if there is no value from the server.
+/// Values loaded asynchronously on the server can be transferred by updating the state
+/// during the render:
+/// ```ignore
+/// let user = use_serialized_state(|| None);
+///
+/// let name = match user.as_ref() {
+///     Some(user) => user.name.clone(),
+///     None => {
+///         let loaded = load_user().await?;
+///         let name = loaded.name.clone();
+///         user.update(move |_| Some(loaded));
+///         name
+///     }
+/// };
+/// ```
+pub fn use_serialized_state<T, D>(default: D) -> State<T>
+where
+    T: Serialize + DeserializeOwned + Send + Sync + 'static,
+    D: FnOnce() -> T,
+{
+    use_serialized_state_with(
+        default,
+        |value| serde_json::from_value(value).ok(),
+        serialize::<T>,
+    )
+}
+
+/// Like [use_serialized_state] but with custom conversions from and to the serialized value,
+/// e.G. to only transfer part of the state.
+pub(crate) fn use_serialized_state_with<T, D>(
+    default: D,
+    restore: fn(Value) -> Option<T>,
+    serializer: Serializer,
+) -> State<T>
+where
+    T: Send + Sync + 'static,
+    D: FnOnce() -> T,
+{
+    with_async_context_mut(|hook: Option<&mut Hook>| {
+        if let Some(hook) = hook {
+            let serialized_state = get_serialized_state(hook);
+            let state = next_state(hook, |hook, index| {
+                serialized_state
+                    .as_ref()
+                    .and_then(|serialized_state| {
+                        serialized_state.take_value(&key(&hook.path, index))
+                    })
+                    .and_then(restore)
+                    .unwrap_or_else(default)
+            });
+            if serialized_state
+                .is_some_and(|serialized_state| serialized_state.mode == Mode::Record)
+            {
+                hook.serializers.insert(hook.state_index - 1, serializer);
+            }
+            state
+        } else {
+            State::mock(default())
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn restore() {
+        let recorded = SerializedState::record();
+        recorded.insert(key("/0", 0), Value::from(42));
+        recorded.insert(key("/0/1", 2), Value::from("</script>"));
+
+        let restored = SerializedState::restore(&recorded.to_json());
+        assert_eq!(restored.take::<i32>("/0:0"), Some(42));
+        assert_eq!(restored.take::<i32>("/0:0"), None);
+        assert_eq!(
+            restored.take::<String>("/0/1:2"),
+            Some("</script>".to_string())
+        );
+        assert!(restored.is_empty());
+
+        assert!(SerializedState::restore("invalid").is_empty());
+    }
+}
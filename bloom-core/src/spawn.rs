@@ -0,0 +1,65 @@
+use std::{
+    pin::Pin,
+    sync::{Arc, Mutex},
+};
+
+use async_context::with_async_context;
+use futures_util::{
+    future::FutureObj,
+    task::{Spawn, SpawnError},
+    Future, FutureExt,
+};
+
+use crate::hook::Hook;
+
+/// The spawner passed to render_loop or render_stream, shared with the hooks of all components.
+pub(crate) type SharedSpawner = Arc<dyn Spawn + Send + Sync>;
+
+/// Spawners only need to be Send, so calls are serialized through a mutex.
+struct SyncSpawner<S>(Mutex<S>);
+
+impl<S> Spawn for SyncSpawner<S>
+where
+    S: Spawn,
+{
+    fn spawn_obj(&self, future: FutureObj<'static, ()>) -> Result<(), SpawnError> {
+        self.0
+            .lock()
+            .map_err(|_| SpawnError::shutdown())?
+            .spawn_obj(future)
+    }
+}
+
+pub(crate) fn share_spawner<S>(spawner: S) -> SharedSpawner
+where
+    S: Spawn + Send + 'static,
+{
+    Arc::new(SyncSpawner(Mutex::new(spawner)))
+}
+
+/// spawn runs a future on the spawner passed to render_loop or render_stream,
+/// so multiple futures can make progress concurrently while a component awaits them:
+/// ```ignore
+/// let user = spawn(load_user(self.user_id));
+/// let posts = spawn(load_posts(self.user_id));
+/// let (user, posts) = join!(user, posts);
+/// ```
+/// Outside of a render, the future is run when the result is awaited.
+pub fn spawn<F>(future: F) -> Pin<Box<dyn Future<Output = F::Output> + Send>>
+where
+    F: Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    let spawner =
+        with_async_context(|hook: Option<&Hook>| hook.and_then(|hook| hook.spawner.clone()));
+    let spawner = match spawner {
+        Some(spawner) => spawner,
+        None => return Box::pin(future),
+    };
+
+    let (remote, handle) = future.remote_handle();
+    spawner
+        .spawn_obj(FutureObj::new(Box::new(remote)))
+        .expect("Failed to spawn async task");
+    Box::pin(handle)
+}
@@ -38,7 +38,6 @@ impl StateUpdate {
 /// my_state.update(|value| *value + 1);
 /// ```
 /// This will trigger a re-render of the component.
-#[derive(Clone)]
 pub struct State<T> {
     value: Arc<T>,
     signal: Sender<()>,
@@ -46,6 +45,17 @@ pub struct State<T> {
     index: u16,
 }
 
+impl<T> Clone for State<T> {
+    fn clone(&self) -> Self {
+        Self {
+            value: self.value.clone(),
+            signal: self.signal.clone(),
+            updater: self.updater.clone(),
+            index: self.index,
+        }
+    }
+}
+
 impl<T> Deref for State<T> {
     type Target = T;
 
@@ -76,7 +86,7 @@ impl<T> State<T>
 where
     T: Send + Sync + 'static,
 {
-    fn mock(value: T) -> Self {
+    pub(crate) fn mock(value: T) -> Self {
         let (mock_signal, _) = bounded(0);
         let (mock_updater, _) = bounded(0);
         State {
@@ -88,6 +98,15 @@ where
     }
 
     pub fn update<C, R>(&self, callback: C)
+    where
+        R: Into<Arc<T>>,
+        C: FnOnce(Arc<T>) -> R + Send + Sync + 'static,
+    {
+        self.try_update(callback).expect("Failed to send update");
+    }
+
+    /// Like update but fails instead of panicking once the component has been unmounted.
+    pub(crate) fn try_update<C, R>(&self, callback: C) -> Result<(), ()>
     where
         R: Into<Arc<T>>,
         C: FnOnce(Arc<T>) -> R + Send + Sync + 'static,
@@ -103,8 +122,37 @@ where
                 }),
                 index: self.index,
             })
-            .expect("Failed to send update");
+            .map_err(|_| ())?;
         let _ = self.signal.try_send(());
+        Ok(())
+    }
+}
+
+/// Reserves the next state slot of the hook.
+/// The slot is initialized using the passed callback on the first render
+/// and kept in the hook's state afterwards.
+pub(crate) fn next_state<T, D>(hook: &mut Hook, default: D) -> State<T>
+where
+    T: Send + Sync + 'static,
+    D: FnOnce(&Hook, u16) -> T,
+{
+    let index = hook.state_index;
+    hook.state_index += 1;
+    let value: Arc<T> = if let Some(value) = hook.state.get(&index) {
+        value
+            .clone()
+            .downcast()
+            .expect("Invalid Hook Call: Type mismatch")
+    } else {
+        let value = Arc::new(default(hook, index));
+        hook.state.insert(index, value.clone());
+        value
+    };
+    State {
+        value,
+        signal: hook.signal.clone(),
+        updater: hook.updater.clone(),
+        index,
     }
 }
 
@@ -118,30 +166,7 @@ where
 {
     with_async_context_mut(|hook: Option<&mut Hook>| {
         if let Some(hook) = hook {
-            let signal = hook.signal.clone();
-            let updater = hook.updater.clone();
-            let index = hook.state_index;
-            hook.state_index += 1;
-            if let Some(value) = hook.state.get(&index) {
-                let value: Arc<T> = value
-                    .clone()
-                    .downcast()
-                    .expect("Invalid Hook Call: Type mismatch");
-                State {
-                    value,
-                    signal,
-                    updater,
-                    index,
-                }
-            } else {
-                let value = Arc::new(default());
-                State {
-                    value,
-                    signal,
-                    updater,
-                    index,
-                }
-            }
+            next_state(hook, |_, _| default())
         } else {
             State::mock(default())
         }
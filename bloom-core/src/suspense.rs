@@ -2,9 +2,54 @@ use futures_util::task::noop_waker;
 use std::{
     future::Future,
     pin::Pin,
+    sync::Arc,
     task::{Context, Poll},
 };
 
+use crate::Element;
+
+/// Marks a subtree that can be streamed out of order during server-side rendering.
+/// If the children are not ready when the stream reaches the boundary,
+/// the fallback is sent in their place and the children follow once they are resolved.
+/// In the render loop, the children are rendered as usual.
+/// ```ignore
+/// Suspense::new(|| rsx!(<Spinner />)).children(vec![rsx!(<Recommendations />)])
+/// ```
+pub struct Suspense<N, E>
+where
+    N: From<String>,
+{
+    fallback: Box<dyn Fn() -> Element<N, E> + Send + Sync>,
+}
+
+impl<N, E> Suspense<N, E>
+where
+    N: From<String> + 'static,
+    E: 'static,
+{
+    pub fn new<F>(fallback: F) -> Self
+    where
+        F: Fn() -> Element<N, E> + Send + Sync + 'static,
+    {
+        Self {
+            fallback: Box::new(fallback),
+        }
+    }
+
+    pub fn children(self, children: Vec<Element<N, E>>) -> Element<N, E> {
+        Element::Provider(Arc::new(self), children)
+    }
+}
+
+impl<N, E> Suspense<N, E>
+where
+    N: From<String>,
+{
+    pub(crate) fn fallback(&self) -> Element<N, E> {
+        (self.fallback)()
+    }
+}
+
 pub(crate) enum RunOrSuspendResult<T> {
     Suspend(Pin<Box<dyn Future<Output = T> + Send>>),
     Done(T),
@@ -0,0 +1,86 @@
+use crate::{OrderedMap, OrderedMapIter};
+
+/// The attributes of an [crate::HtmlElement].
+/// Attributes keep the order in which they were first set,
+/// so rendering the same element always yields the same output.
+pub type Attributes = OrderedMap<str>;
+
+/// Attributes that are true if present, regardless of their value.
+const BOOLEAN_ATTRIBUTES: &[&str] = &[
+    "allowfullscreen",
+    "async",
+    "autofocus",
+    "autoplay",
+    "checked",
+    "controls",
+    "default",
+    "defer",
+    "disabled",
+    "formnovalidate",
+    "inert",
+    "ismap",
+    "itemscope",
+    "loop",
+    "multiple",
+    "muted",
+    "nomodule",
+    "novalidate",
+    "open",
+    "playsinline",
+    "readonly",
+    "required",
+    "reversed",
+    "selected",
+];
+
+/// Whether the attribute is true if present, regardless of its value, e.G. `disabled`.
+/// Renderers omit boolean attributes set to `"false"`, see [is_rendered_attribute].
+/// `hidden` is not a boolean attribute since it can also be `"until-found"`.
+pub fn is_boolean_attribute(name: &str) -> bool {
+    BOOLEAN_ATTRIBUTES.contains(&name)
+}
+
+/// Whether the attribute ends up in the DOM:
+/// Boolean attributes set to `"false"` are left out, since they would be true otherwise.
+/// bloom-ssr and bloom-client both follow this rule so server and client output match.
+pub fn is_rendered_attribute(name: &str, value: &str) -> bool {
+    !(is_boolean_attribute(name) && value == "false")
+}
+
+/// The iterator returned from [Attributes::iter].
+pub type AttributesIter<'a> = OrderedMapIter<'a, str>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn insertion_order() {
+        let mut attributes = Attributes::new();
+        attributes.insert("id".to_string(), "foo".to_string());
+        attributes.insert("class".to_string(), "bar".to_string());
+        attributes.insert("data-x".to_string(), "1".to_string());
+        assert_eq!(
+            attributes.insert("id".to_string(), "baz".to_string()),
+            Some("foo".to_string())
+        );
+
+        assert_eq!(
+            attributes.iter().collect::<Vec<_>>(),
+            vec![("id", "baz"), ("class", "bar"), ("data-x", "1")]
+        );
+
+        assert_eq!(attributes.remove("class"), Some("bar".to_string()));
+        assert_eq!(attributes.keys().collect::<Vec<_>>(), vec!["id", "data-x"]);
+        assert_eq!(attributes.get("data-x"), Some("1"));
+        assert!(!attributes.contains_key("class"));
+    }
+
+    #[test]
+    fn rendered_attributes() {
+        assert!(!is_rendered_attribute("disabled", "false"));
+        assert!(is_rendered_attribute("disabled", ""));
+        assert!(is_rendered_attribute("hidden", "until-found"));
+        assert!(is_rendered_attribute("draggable", "false"));
+    }
+}
@@ -1,11 +1,18 @@
-use std::{collections::HashMap, fmt::Debug, sync::Arc};
+use std::{borrow::Cow, collections::HashMap, fmt::Debug, sync::Arc};
 
-use crate::{DomRef, EventHandler};
+use crate::styles::to_css;
+use crate::{
+    is_rendered_attribute, Attributes, Classes, DomRef, EventHandler, EventOptions, Properties,
+    PropertyValue, Styles,
+};
 
 /// Represents an html tag such as `<div>`, `<span>`, etc.
 pub struct HtmlElement {
     pub(crate) tag_name: &'static str,
-    pub(crate) attributes: HashMap<String, String>,
+    pub(crate) attributes: Attributes,
+    pub(crate) properties: Properties,
+    pub(crate) styles: Styles,
+    pub(crate) classes: Classes,
     pub(crate) callbacks: HashMap<String, EventHandler>,
     pub(crate) dom_ref: Option<Arc<DomRef>>,
 }
@@ -15,7 +22,10 @@ impl Debug for HtmlElement {
         f.debug_struct("HtmlElement")
             .field("tag_name", &self.tag_name)
             .field("attributes", &self.attributes)
-            .field("callbacks", &"Callbacks")
+            .field("properties", &self.properties)
+            .field("styles", &self.styles)
+            .field("classes", &self.classes)
+            .field("callbacks", &self.callbacks.keys())
             .field("dom_ref", &self.dom_ref)
             .finish()
     }
@@ -29,7 +39,10 @@ impl HtmlElement {
     pub fn new() -> HtmlElementBuilder<()> {
         HtmlElementBuilder {
             tag_name: (),
-            attributes: HashMap::new(),
+            attributes: Attributes::new(),
+            properties: Properties::new(),
+            styles: Styles::new(),
+            classes: Classes::new(),
             callbacks: HashMap::new(),
             dom_ref: None,
         }
@@ -39,13 +52,84 @@ impl HtmlElement {
         &self.tag_name
     }
 
-    /// get a map of all the attributes:
-    /// For a `<div id="foo" class="bar">` this would return
-    /// `{"id": "foo", "class": "bar"}`
-    pub fn attributes(&self) -> &HashMap<String, String> {
+    /// get all the attributes in the order they were set:
+    /// For a `<div id="foo" class="bar">` this would iterate over
+    /// `("id", "foo"), ("class", "bar")`
+    pub fn attributes(&self) -> &Attributes {
         &self.attributes
     }
 
+    /// get the value of one specific attribute
+    pub fn attribute(&self, key: &str) -> Option<&str> {
+        self.attributes.get(key)
+    }
+
+    /// get all the DOM properties in the order they were set
+    pub fn properties(&self) -> &Properties {
+        &self.properties
+    }
+
+    /// get the inline styles set via `style`
+    pub fn styles(&self) -> &Styles {
+        &self.styles
+    }
+
+    /// get the classes added via `class` and `class_if`
+    pub fn classes(&self) -> &Classes {
+        &self.classes
+    }
+
+    /// get the value of the `class` attribute, including the classes added via `class`
+    pub fn class_name(&self) -> Option<Cow<'_, str>> {
+        let attribute = self.attributes.get("class");
+        if self.classes.is_empty() {
+            return attribute.map(Cow::Borrowed);
+        }
+        let classes = attribute
+            .into_iter()
+            .flat_map(str::split_whitespace)
+            .chain(self.classes.iter().filter(|class| {
+                !attribute
+                    .is_some_and(|attribute| attribute.split_whitespace().any(|c| c == *class))
+            }))
+            .collect::<Vec<_>>();
+        Some(Cow::Owned(classes.join(" ")))
+    }
+
+    /// get the value of the `style` attribute, including the styles set via `style`
+    pub fn style_text(&self) -> Option<Cow<'_, str>> {
+        let attribute = self.attributes.get("style");
+        if self.styles.is_empty() {
+            return attribute.map(Cow::Borrowed);
+        }
+        Some(Cow::Owned(
+            match attribute.map(|style| style.trim().trim_end_matches(';')) {
+                Some(style) if !style.is_empty() => format!("{}; {}", style, to_css(&self.styles)),
+                _ => to_css(&self.styles),
+            },
+        ))
+    }
+
+    /// get the attributes as they are rendered,
+    /// with the classes and styles merged into the `class` and `style` attributes
+    /// and without boolean attributes set to `"false"`, see [is_rendered_attribute]
+    pub fn rendered_attributes(&self) -> impl Iterator<Item = (&str, Cow<'_, str>)> {
+        let class = (!self.classes.is_empty() && !self.attributes.contains_key("class"))
+            .then(|| ("class", self.class_name().unwrap_or_default()));
+        let style = (!self.styles.is_empty() && !self.attributes.contains_key("style"))
+            .then(|| ("style", self.style_text().unwrap_or_default()));
+        self.attributes
+            .iter()
+            .filter(|(name, value)| is_rendered_attribute(name, value))
+            .map(|(name, value)| match name {
+                "class" => (name, self.class_name().unwrap_or_default()),
+                "style" => (name, self.style_text().unwrap_or_default()),
+                _ => (name, Cow::Borrowed(value)),
+            })
+            .chain(class)
+            .chain(style)
+    }
+
     /// get a map of all the callbacks / event handlers:
     /// For a `<div on_click=|_| { alert!("clicked")}>` this would return
     /// `{ "click": |event| { alert!("clicked") } }`
@@ -61,16 +145,38 @@ impl HtmlElement {
 
 pub struct HtmlElementBuilder<T> {
     pub(crate) tag_name: T,
-    pub(crate) attributes: HashMap<String, String>,
+    pub(crate) attributes: Attributes,
+    pub(crate) properties: Properties,
+    pub(crate) styles: Styles,
+    pub(crate) classes: Classes,
     pub(crate) callbacks: HashMap<String, EventHandler>,
     pub(crate) dom_ref: Option<Arc<DomRef>>,
 }
 
+/// The tag name of an [HtmlElementBuilder]:
+/// Either a `&'static str` or one of the types in [crate::tag::elements],
+/// which also provide typed setters for the attributes of their element.
+pub trait TagName {
+    fn tag_name(&self) -> &'static str;
+}
+
+impl TagName for &'static str {
+    fn tag_name(&self) -> &'static str {
+        self
+    }
+}
+
 impl HtmlElementBuilder<()> {
-    pub fn tag_name(self, tag_name: &'static str) -> HtmlElementBuilder<&'static str> {
+    pub fn tag_name<T>(self, tag_name: T) -> HtmlElementBuilder<T>
+    where
+        T: TagName,
+    {
         HtmlElementBuilder {
             tag_name,
             attributes: self.attributes,
+            properties: self.properties,
+            styles: self.styles,
+            classes: self.classes,
             callbacks: self.callbacks,
             dom_ref: self.dom_ref,
         }
@@ -93,17 +199,105 @@ impl<T> HtmlElementBuilder<T> {
         self
     }
 
+    /// set a DOM property of the element.
+    /// Unlike attributes, properties are applied on every render,
+    /// so e.G. a controlled input does not drift from the state after the user typed:
+    /// ```ignore
+    /// HtmlElement::new().tag_name("input").prop("value", &*text).build();
+    /// ```
+    /// During server-side rendering, `value`, `checked` and `selected` are rendered as attributes.
+    pub fn prop<K, V>(mut self, key: K, value: V) -> Self
+    where
+        K: Into<String>,
+        V: Into<PropertyValue>,
+    {
+        self.properties.insert(key.into(), value.into());
+        self
+    }
+
+    /// set an inline style property, using its CSS name:
+    /// ```ignore
+    /// HtmlElement::new().tag_name("div").style("background-color", "red").style("opacity", 0.5).build();
+    /// ```
+    /// The styles are merged into the `style` attribute, if there is one.
+    pub fn style<K, V>(mut self, key: K, value: V) -> Self
+    where
+        K: Into<String>,
+        V: ToString,
+    {
+        self.styles.insert(key.into(), value.to_string());
+        self
+    }
+
+    /// add one or more whitespace-separated classes:
+    /// ```ignore
+    /// HtmlElement::new().tag_name("div").class("card").class_if("active", is_active).build();
+    /// ```
+    /// The classes are merged into the `class` attribute, if there is one.
+    pub fn class<C>(mut self, name: C) -> Self
+    where
+        C: AsRef<str>,
+    {
+        for class in name.as_ref().split_whitespace() {
+            self.classes.insert(class.to_string());
+        }
+        self
+    }
+
+    /// add one or more classes if the condition is true
+    pub fn class_if<C>(self, name: C, condition: bool) -> Self
+    where
+        C: AsRef<str>,
+    {
+        if condition {
+            self.class(name)
+        } else {
+            self
+        }
+    }
+
     /// Set one specific callback / event handler:
     /// ```
     /// HtmlElement::new().tag_name("div").on("click", |event| { alert!("clicked") }).build();
     /// ```
-    /// builds a div that will send an alert on the "click"-event
-    pub fn on<K, C>(mut self, key: K, handler: C) -> Self
+    /// builds a div that will send an alert on the "click"-event.
+    /// The standard events also have typed methods such as `on_click`.
+    /// The key is the name of the event as is, e.G. `"htmx:afterSwap"`,
+    /// use `modifiers` to set [EventOptions].
+    pub fn on<K, C>(self, key: K, handler: C) -> Self
     where
-        K: Into<String>,
+        K: AsRef<str>,
         C: Fn(web_sys::Event) + Send + Sync + 'static,
     {
-        self.callbacks.insert(key.into(), Box::new(handler));
+        self.handler(key, handler)
+    }
+
+    /// Set an [EventHandler] that was created beforehand.
+    /// Reusing the same handler across renders keeps the element equal to its previous version:
+    /// ```ignore
+    /// HtmlElement::new().tag_name("div").handler("click", on_click.clone()).build();
+    /// ```
+    pub fn handler<K, H>(mut self, key: K, handler: H) -> Self
+    where
+        K: AsRef<str>,
+        H: Into<EventHandler>,
+    {
+        self.callbacks
+            .insert(key.as_ref().to_string(), handler.into());
+        self
+    }
+
+    /// Apply [EventOptions] such as `prevent_default` to the handler that is already set for an event,
+    /// e.G. after one of the typed methods:
+    /// ```ignore
+    /// HtmlElement::new().tag_name("form").on_submit(handler).modifiers("submit", EventOptions::new().prevent_default()).build();
+    /// ```
+    pub fn modifiers(mut self, event: &str, options: EventOptions) -> Self {
+        if let Some(handler) = self.callbacks.remove(event) {
+            let options = handler.options().union(options);
+            self.callbacks
+                .insert(event.to_string(), handler.with_options(options));
+        }
         self
     }
 
@@ -115,11 +309,17 @@ impl<T> HtmlElementBuilder<T> {
     }
 }
 
-impl HtmlElementBuilder<&'static str> {
+impl<T> HtmlElementBuilder<T>
+where
+    T: TagName,
+{
     pub fn build(self) -> HtmlElement {
         HtmlElement {
-            tag_name: self.tag_name,
+            tag_name: self.tag_name.tag_name(),
             attributes: self.attributes,
+            properties: self.properties,
+            styles: self.styles,
+            classes: self.classes,
             callbacks: self.callbacks,
             dom_ref: self.dom_ref,
         }
@@ -130,8 +330,10 @@ impl PartialEq for HtmlElement {
     fn eq(&self, other: &Self) -> bool {
         self.tag_name == other.tag_name
             && self.attributes == other.attributes
-            && self.callbacks.is_empty()
-            && other.callbacks.is_empty()
+            && self.properties == other.properties
+            && self.styles == other.styles
+            && self.classes == other.classes
+            && self.callbacks == other.callbacks
             && self.dom_ref == other.dom_ref
     }
 }
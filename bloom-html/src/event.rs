@@ -1,11 +1,274 @@
-/// Event handlers are basically just closures that take a web_sys::Event as an argument.
-/// This type only provides a convenience for implementing the actual render-functions.
-pub type EventHandler = Box<dyn Fn(web_sys::Event) + Send + Sync + 'static>;
+use std::{fmt::Debug, sync::Arc};
+
+use web_sys::{
+    wasm_bindgen::JsCast, AnimationEvent, ClipboardEvent, CompositionEvent, DragEvent, Event,
+    FocusEvent, InputEvent, KeyboardEvent, MouseEvent, PointerEvent, SubmitEvent, TouchEvent,
+    TransitionEvent, WheelEvent,
+};
+
+use crate::element::HtmlElementBuilder;
+
+/// Event handlers are closures that take a web_sys::Event or one of its subtypes as an argument.
+/// They are compared by identity, so an element whose handlers were created once,
+/// e.G. in a `use_ref`, and cloned on every render compares equal to its previous version.
+/// ```ignore
+/// let on_click = EventHandler::new(|event: MouseEvent| log(event.client_x()));
+/// div().handler("click", on_click.clone()).build();
+/// ```
+#[derive(Clone)]
+pub struct EventHandler {
+    handler: Arc<dyn Fn(Event) + Send + Sync + 'static>,
+    options: EventOptions,
+}
+
+impl EventHandler {
+    /// Wrap a closure as an event handler.
+    /// The handler is skipped for events that are not of the type the closure expects,
+    /// e.G. a plain `Event` dispatched via `dispatchEvent` to a `MouseEvent` handler.
+    pub fn new<E, C>(handler: C) -> Self
+    where
+        E: JsCast,
+        C: Fn(E) + Send + Sync + 'static,
+    {
+        Self {
+            handler: Arc::new(move |event: Event| {
+                if let Ok(event) = event.dyn_into::<E>() {
+                    handler(event)
+                }
+            }),
+            options: EventOptions::default(),
+        }
+    }
+
+    /// Run the handler for an event
+    pub fn call(&self, event: Event) {
+        (self.handler)(event)
+    }
+
+    /// get the listener options and modifiers of the handler
+    pub fn options(&self) -> EventOptions {
+        self.options
+    }
+
+    pub fn with_options(mut self, options: EventOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Listen passively, e.G. for scroll and touch events the browser can then scroll
+    /// without waiting for the handler. Passive handlers cannot prevent the default action.
+    pub fn passive(mut self) -> Self {
+        self.options.passive = true;
+        self
+    }
+
+    /// Run the handler in the capture phase, before the handlers of the elements inside.
+    pub fn capture(mut self) -> Self {
+        self.options.capture = true;
+        self
+    }
+
+    /// Run the handler only for the first event.
+    pub fn once(mut self) -> Self {
+        self.options.once = true;
+        self
+    }
+
+    /// Call `event.preventDefault()` before the handler runs.
+    pub fn prevent_default(mut self) -> Self {
+        self.options.prevent_default = true;
+        self
+    }
+
+    /// Call `event.stopPropagation()` before the handler runs.
+    pub fn stop_propagation(mut self) -> Self {
+        self.options.stop_propagation = true;
+        self
+    }
+}
+
+/// The listener options and declarative modifiers of an [EventHandler]:
+/// ```ignore
+/// form().on_submit(handler).modifiers("submit", EventOptions::new().prevent_default()).build();
+/// ```
+/// In rsx, the modifiers `passive`, `capture`, `once`, `prevent` and `stop`
+/// can be appended to the event attribute, e.G. `<form on_submit:prevent={handler}>`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct EventOptions {
+    pub passive: bool,
+    pub capture: bool,
+    pub once: bool,
+    pub prevent_default: bool,
+    pub stop_propagation: bool,
+}
+
+impl EventOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// see [EventHandler::passive]
+    pub fn passive(mut self) -> Self {
+        self.passive = true;
+        self
+    }
+
+    /// see [EventHandler::capture]
+    pub fn capture(mut self) -> Self {
+        self.capture = true;
+        self
+    }
+
+    /// see [EventHandler::once]
+    pub fn once(mut self) -> Self {
+        self.once = true;
+        self
+    }
+
+    /// see [EventHandler::prevent_default]
+    pub fn prevent_default(mut self) -> Self {
+        self.prevent_default = true;
+        self
+    }
+
+    /// see [EventHandler::stop_propagation]
+    pub fn stop_propagation(mut self) -> Self {
+        self.stop_propagation = true;
+        self
+    }
+
+    /// Combine the options, each one is set if it is set in either of them.
+    pub(crate) fn union(self, other: Self) -> Self {
+        Self {
+            passive: self.passive || other.passive,
+            capture: self.capture || other.capture,
+            once: self.once || other.once,
+            prevent_default: self.prevent_default || other.prevent_default,
+            stop_propagation: self.stop_propagation || other.stop_propagation,
+        }
+    }
+}
+
+impl<C> From<C> for EventHandler
+where
+    C: Fn(Event) + Send + Sync + 'static,
+{
+    fn from(handler: C) -> Self {
+        Self {
+            handler: Arc::new(handler),
+            options: EventOptions::default(),
+        }
+    }
+}
+
+impl PartialEq for EventHandler {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.handler, &other.handler) && self.options == other.options
+    }
+}
+
+impl Debug for EventHandler {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EventHandler")
+            .field("options", &self.options)
+            .finish()
+    }
+}
+
+macro_rules! typed_handlers {
+    ($($method:ident: $name:literal => $event:ty,)*) => {
+        /// The events with a typed handler method, e.G. `click` for `on_click`.
+        pub const TYPED_EVENTS: &[&str] = &[$($name),*];
+
+        impl<T> HtmlElementBuilder<T> {
+            $(
+                #[doc = concat!("Set the handler for the `", $name, "`-event, which receives a [", stringify!($event), "]")]
+                pub fn $method<C>(self, handler: C) -> Self
+                where
+                    C: Fn($event) + Send + Sync + 'static,
+                {
+                    self.handler($name, EventHandler::new(handler))
+                }
+            )*
+        }
+    };
+}
+
+typed_handlers! {
+    on_click: "click" => MouseEvent,
+    on_dblclick: "dblclick" => MouseEvent,
+    on_auxclick: "auxclick" => MouseEvent,
+    on_contextmenu: "contextmenu" => MouseEvent,
+    on_mousedown: "mousedown" => MouseEvent,
+    on_mouseup: "mouseup" => MouseEvent,
+    on_mousemove: "mousemove" => MouseEvent,
+    on_mouseover: "mouseover" => MouseEvent,
+    on_mouseout: "mouseout" => MouseEvent,
+    on_mouseenter: "mouseenter" => MouseEvent,
+    on_mouseleave: "mouseleave" => MouseEvent,
+    on_pointerdown: "pointerdown" => PointerEvent,
+    on_pointerup: "pointerup" => PointerEvent,
+    on_pointermove: "pointermove" => PointerEvent,
+    on_pointerover: "pointerover" => PointerEvent,
+    on_pointerout: "pointerout" => PointerEvent,
+    on_pointerenter: "pointerenter" => PointerEvent,
+    on_pointerleave: "pointerleave" => PointerEvent,
+    on_pointercancel: "pointercancel" => PointerEvent,
+    on_gotpointercapture: "gotpointercapture" => PointerEvent,
+    on_lostpointercapture: "lostpointercapture" => PointerEvent,
+    on_keydown: "keydown" => KeyboardEvent,
+    on_keyup: "keyup" => KeyboardEvent,
+    on_keypress: "keypress" => KeyboardEvent,
+    // Checkboxes, radio buttons and selects fire a plain `Event`
+    on_input: "input" => Event,
+    on_beforeinput: "beforeinput" => InputEvent,
+    on_focus: "focus" => FocusEvent,
+    on_blur: "blur" => FocusEvent,
+    on_focusin: "focusin" => FocusEvent,
+    on_focusout: "focusout" => FocusEvent,
+    on_wheel: "wheel" => WheelEvent,
+    on_touchstart: "touchstart" => TouchEvent,
+    on_touchend: "touchend" => TouchEvent,
+    on_touchmove: "touchmove" => TouchEvent,
+    on_touchcancel: "touchcancel" => TouchEvent,
+    on_drag: "drag" => DragEvent,
+    on_dragstart: "dragstart" => DragEvent,
+    on_dragend: "dragend" => DragEvent,
+    on_dragenter: "dragenter" => DragEvent,
+    on_dragleave: "dragleave" => DragEvent,
+    on_dragover: "dragover" => DragEvent,
+    on_drop: "drop" => DragEvent,
+    on_submit: "submit" => SubmitEvent,
+    on_copy: "copy" => ClipboardEvent,
+    on_cut: "cut" => ClipboardEvent,
+    on_paste: "paste" => ClipboardEvent,
+    on_compositionstart: "compositionstart" => CompositionEvent,
+    on_compositionupdate: "compositionupdate" => CompositionEvent,
+    on_compositionend: "compositionend" => CompositionEvent,
+    on_animationstart: "animationstart" => AnimationEvent,
+    on_animationend: "animationend" => AnimationEvent,
+    on_animationiteration: "animationiteration" => AnimationEvent,
+    on_animationcancel: "animationcancel" => AnimationEvent,
+    on_transitionstart: "transitionstart" => TransitionEvent,
+    on_transitionend: "transitionend" => TransitionEvent,
+    on_transitionrun: "transitionrun" => TransitionEvent,
+    on_transitioncancel: "transitioncancel" => TransitionEvent,
+    on_change: "change" => Event,
+    on_reset: "reset" => Event,
+    on_invalid: "invalid" => Event,
+    on_select: "select" => Event,
+    on_scroll: "scroll" => Event,
+    on_load: "load" => Event,
+    on_error: "error" => Event,
+    on_toggle: "toggle" => Event,
+}
 
 #[cfg(test)]
 mod tests {
 
-    use crate::{tag::button, HtmlElement};
+    use web_sys::MouseEvent;
+
+    use crate::{tag::button, tag::div, EventHandler, EventOptions, HtmlElement};
 
     #[test]
     fn build_button() {
@@ -13,4 +276,63 @@ mod tests {
 
         let _cb = button.callbacks().get("click");
     }
+
+    #[test]
+    fn build_typed_handler() {
+        let button: HtmlElement = button().on_click(|_: MouseEvent| {}).build();
+
+        assert!(button.callbacks().contains_key("click"));
+    }
+
+    #[test]
+    fn compare_handlers_by_identity() {
+        let handler = EventHandler::new(|_: MouseEvent| {});
+
+        assert_eq!(
+            div().handler("click", handler.clone()).build(),
+            div().handler("click", handler).build()
+        );
+        assert_ne!(
+            div().on_click(|_| {}).build(),
+            div().on_click(|_| {}).build()
+        );
+    }
+
+    #[test]
+    fn apply_modifiers() {
+        let form: HtmlElement = HtmlElement::new()
+            .tag_name("form")
+            .on("submit", |_| {})
+            .modifiers(
+                "submit",
+                EventOptions::new().prevent_default().stop_propagation(),
+            )
+            .build();
+
+        assert_eq!(
+            form.callbacks().get("submit").unwrap().options(),
+            EventOptions {
+                prevent_default: true,
+                stop_propagation: true,
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn modify_typed_handler() {
+        let div: HtmlElement = div()
+            .on_scroll(|_| {})
+            .modifiers("scroll", EventOptions::new().passive())
+            .build();
+
+        assert!(div.callbacks().get("scroll").unwrap().options().passive);
+    }
+
+    #[test]
+    fn keep_namespaced_event_names() {
+        let div: HtmlElement = div().on("htmx:afterSwap", |_| {}).build();
+
+        assert!(div.callbacks().contains_key("htmx:afterSwap"));
+    }
 }
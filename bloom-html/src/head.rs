@@ -0,0 +1,250 @@
+use std::{
+    hash::{Hash, Hasher},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+};
+
+use bloom_core::{use_context, use_effect, use_ref_with_default};
+
+use crate::{
+    element::{HtmlElementBuilder, TagName},
+    tag, HtmlElement,
+};
+
+/// The attribute that identifies elements managed by [use_head] in the document head.
+pub const HEAD_KEY_ATTRIBUTE: &str = "data-bloom-head";
+
+/// An element in the document head such as `<title>`, `<meta>` or `<link>`.
+/// Tags with the same key are deduplicated, the one set last wins.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HeadTag {
+    key: String,
+    element: Arc<HtmlElement>,
+    text: Option<String>,
+}
+
+impl HeadTag {
+    /// Create a custom head tag:
+    /// ```ignore
+    /// HeadTag::new("preload:/font.woff2", tag("link").attr("rel", "preload").attr("href", "/font.woff2"))
+    /// ```
+    pub fn new<K, T>(key: K, element: HtmlElementBuilder<T>) -> Self
+    where
+        K: Into<String>,
+        T: TagName,
+    {
+        let key = key.into();
+        Self {
+            element: Arc::new(element.attr(HEAD_KEY_ATTRIBUTE, key.clone()).build()),
+            key,
+            text: None,
+        }
+    }
+
+    /// Set the text content, e.G. of a `<title>`.
+    pub fn text<T>(mut self, text: T) -> Self
+    where
+        T: Into<String>,
+    {
+        self.text = Some(text.into());
+        self
+    }
+
+    /// `<title>{text}</title>`
+    pub fn title<T>(text: T) -> Self
+    where
+        T: Into<String>,
+    {
+        Self::new("title", tag("title")).text(text)
+    }
+
+    /// `<meta name="{name}" content="{content}">`
+    pub fn meta<N, C>(name: N, content: C) -> Self
+    where
+        N: Into<String>,
+        C: Into<String>,
+    {
+        let name = name.into();
+        Self::new(
+            format!("meta:{}", name),
+            tag("meta").attr("name", name).attr("content", content),
+        )
+    }
+
+    /// `<meta property="{property}" content="{content}">`, e.G. for open graph tags.
+    pub fn meta_property<P, C>(property: P, content: C) -> Self
+    where
+        P: Into<String>,
+        C: Into<String>,
+    {
+        let property = property.into();
+        Self::new(
+            format!("meta:{}", property),
+            tag("meta")
+                .attr("property", property)
+                .attr("content", content),
+        )
+    }
+
+    /// `<link rel="{rel}" href="{href}">`, e.G. for preload hints.
+    /// Links are deduplicated by rel and href.
+    pub fn link<R, H>(rel: R, href: H) -> Self
+    where
+        R: Into<String>,
+        H: Into<String>,
+    {
+        let (rel, href) = (rel.into(), href.into());
+        Self::new(
+            format!("link:{}:{}", rel, href),
+            tag("link").attr("rel", rel).attr("href", href),
+        )
+    }
+
+    /// `<link rel="canonical" href="{href}">`
+    pub fn canonical<H>(href: H) -> Self
+    where
+        H: Into<String>,
+    {
+        Self::new(
+            "link:canonical",
+            tag("link").attr("rel", "canonical").attr("href", href),
+        )
+    }
+
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    pub fn element(&self) -> &HtmlElement {
+        &self.element
+    }
+
+    pub fn text_content(&self) -> Option<&str> {
+        self.text.as_deref()
+    }
+}
+
+#[derive(Default)]
+struct HeadState {
+    owners: Vec<(usize, Vec<HeadTag>)>,
+    version: u64,
+}
+
+/// Collects the head tags of all components using [use_head].
+/// bloom-ssr provides an instance via context and renders the collected tags into `<head>`,
+/// bloom-client reconciles them into `document.head`.
+#[derive(Clone, Default)]
+pub struct Head(Arc<Mutex<HeadState>>);
+
+impl Head {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the tags of one owner, replacing the ones it set before.
+    pub fn set(&self, owner: usize, tags: Vec<HeadTag>) {
+        let mut state = self.0.lock().expect("Failed to lock head");
+        match state.owners.iter_mut().find(|(id, _)| *id == owner) {
+            Some((_, current)) if *current == tags => return,
+            Some((_, current)) => *current = tags,
+            None => state.owners.push((owner, tags)),
+        }
+        state.version += 1;
+    }
+
+    pub fn remove(&self, owner: usize) {
+        let mut state = self.0.lock().expect("Failed to lock head");
+        let count = state.owners.len();
+        state.owners.retain(|(id, _)| *id != owner);
+        if state.owners.len() != count {
+            state.version += 1;
+        }
+    }
+
+    /// The deduplicated tags in the order their keys were first set.
+    /// For each key, the tag set by the most recently registered owner wins,
+    /// so deeper components override their ancestors.
+    pub fn tags(&self) -> Vec<HeadTag> {
+        let state = self.0.lock().expect("Failed to lock head");
+        let mut tags: Vec<HeadTag> = Vec::new();
+        for tag in state.owners.iter().flat_map(|(_, tags)| tags) {
+            match tags.iter_mut().find(|current| current.key == tag.key) {
+                Some(current) => *current = tag.clone(),
+                None => tags.push(tag.clone()),
+            }
+        }
+        tags
+    }
+
+    /// Changes whenever the tags change.
+    pub fn version(&self) -> u64 {
+        self.0.lock().expect("Failed to lock head").version
+    }
+}
+
+static NEXT_OWNER: AtomicUsize = AtomicUsize::new(0);
+
+struct HeadOwner {
+    head: Head,
+    id: usize,
+}
+
+impl Hash for HeadOwner {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.id.hash(state);
+    }
+}
+
+/// use_head sets tags in the document head from anywhere in the tree.
+/// The tags are removed again once the component is unmounted.
+/// ```ignore
+/// use_head(vec![
+///     HeadTag::title(format!("{} | My Blog", self.title)),
+///     HeadTag::meta("description", &self.summary),
+///     HeadTag::canonical(&self.url),
+/// ]);
+/// ```
+pub fn use_head(tags: Vec<HeadTag>) {
+    let head = use_context::<Head>();
+    let owner = use_ref_with_default(|| HeadOwner {
+        head: head.as_ref().clone(),
+        id: NEXT_OWNER.fetch_add(1, Ordering::Relaxed),
+    });
+    owner.head.set(owner.id, tags);
+    use_effect(owner, |owner| move || owner.head.remove(owner.id));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deduplicate() {
+        let head = Head::new();
+        head.set(
+            0,
+            vec![
+                HeadTag::title("App"),
+                HeadTag::meta("description", "An app"),
+            ],
+        );
+        head.set(1, vec![HeadTag::title("Page")]);
+        let version = head.version();
+        head.set(1, vec![HeadTag::title("Page")]);
+        assert_eq!(head.version(), version);
+
+        let tags = head.tags();
+        assert_eq!(tags.len(), 2);
+        assert_eq!(tags[0].text_content(), Some("Page"));
+        assert_eq!(tags[1].element().attribute("content"), Some("An app"));
+        assert_eq!(
+            tags[1].element().attribute(HEAD_KEY_ATTRIBUTE),
+            Some("meta:description")
+        );
+
+        head.remove(1);
+        assert_eq!(head.tags()[0].text_content(), Some("App"));
+    }
+}
@@ -1,15 +1,43 @@
+mod attributes;
 mod comment;
 mod dom_ref;
 mod element;
 mod event;
+mod head;
 mod node;
+mod ordered_map;
+mod properties;
+mod styles;
 pub mod tag;
 
+pub use attributes::{is_boolean_attribute, is_rendered_attribute, Attributes, AttributesIter};
 use bloom_core::Element;
 pub use dom_ref::DomRef;
-pub use element::HtmlElement;
-pub use event::EventHandler;
+pub use element::{HtmlElement, HtmlElementBuilder, TagName};
+pub use event::{EventHandler, EventOptions, TYPED_EVENTS};
+pub use head::{use_head, Head, HeadTag, HEAD_KEY_ATTRIBUTE};
 pub use node::{tag, HtmlNode};
+pub use ordered_map::{OrderedMap, OrderedMapIter};
+pub use properties::{is_reflected_property, Properties, PropertiesIter, PropertyValue};
+pub use styles::{Classes, Styles, StylesIter};
+pub use tag::is_void_element;
+
+/// The id of the script element that carries the serialized state
+/// from server-side rendering to hydration.
+pub const SERIALIZED_STATE_ID: &str = "bloom-serialized-state";
+
+/// The comments bloom-ssr puts around the output of components and fragments.
+/// bloom-client removes them during hydration.
+pub const FRAGMENT_START_MARKER: &str = "[";
+pub const FRAGMENT_END_MARKER: &str = "]";
+
+/// The comment bloom-ssr puts between adjacent text nodes and in place of empty text nodes,
+/// so each text node can be matched during hydration.
+pub const TEXT_MARKER: &str = "|";
+
+/// The comment bloom-ssr puts into elements it had to close before all of their children were rendered,
+/// e.G. at the deadline of the stream. bloom-client renders the missing children during hydration.
+pub const CLIENT_RENDER_MARKER: &str = "?";
 
 /// shortcut for generating text-nodes
 pub fn text<E, T>(text: T) -> Element<HtmlNode, E>
@@ -24,4 +52,6 @@ where
 pub mod prelude {
     /// The `tag`-function rsx will use to generate HtmlElements
     pub use super::tag;
+    /// The options rsx will use for event modifiers such as `on_submit:prevent`
+    pub use super::EventOptions;
 }
@@ -5,7 +5,7 @@ use bloom_core::Element;
 use crate::{
     comment::{HtmlComment, HtmlCommentBuilder},
     element::HtmlElementBuilder,
-    HtmlElement,
+    is_void_element, HtmlElement,
 };
 
 /// The Node-type to use bloom in Browser-Environments.
@@ -70,7 +70,13 @@ impl From<HtmlComment> for HtmlNode {
 }
 
 impl HtmlElement {
+    /// Void elements such as `<br>` or `<input>` cannot have children.
     pub fn children<E>(self, children: Vec<Element<HtmlNode, E>>) -> Element<HtmlNode, E> {
+        debug_assert!(
+            children.is_empty() || !is_void_element(self.tag_name()),
+            "<{}> is a void element and cannot have children",
+            self.tag_name()
+        );
         Element::Node(HtmlNode::Element(Arc::new(self)), children)
     }
 }
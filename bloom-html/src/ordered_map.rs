@@ -0,0 +1,144 @@
+use std::{borrow::Borrow, fmt::Debug, iter::Map, slice::Iter};
+
+type Entry<V> = (String, <V as ToOwned>::Owned);
+
+/// A map that keeps its entries in the order in which they were first inserted,
+/// so rendering the same element always yields the same output.
+/// Values are stored owned and borrowed like [std::borrow::Cow],
+/// e.G. an `OrderedMap<str>` stores `String`s and hands out `&str`s.
+/// Elements only have a handful of entries, so lookups are linear.
+pub struct OrderedMap<V: ?Sized + ToOwned>(Vec<Entry<V>>);
+
+impl<V: ?Sized + ToOwned> OrderedMap<V> {
+    pub fn new() -> Self {
+        Self(Vec::new())
+    }
+
+    /// Set an entry.
+    /// Setting an existing key replaces its value but keeps its position.
+    /// Returns the previous value.
+    pub fn insert(&mut self, key: String, value: V::Owned) -> Option<V::Owned> {
+        match self.0.iter_mut().find(|(name, _)| *name == key) {
+            Some((_, current)) => Some(std::mem::replace(current, value)),
+            None => {
+                self.0.push((key, value));
+                None
+            }
+        }
+    }
+
+    pub fn remove(&mut self, key: &str) -> Option<V::Owned> {
+        let index = self.0.iter().position(|(name, _)| name == key)?;
+        Some(self.0.remove(index).1)
+    }
+
+    pub fn get(&self, key: &str) -> Option<&V> {
+        self.0
+            .iter()
+            .find(|(name, _)| name == key)
+            .map(|(_, value)| value.borrow())
+    }
+
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.0.iter().any(|(name, _)| name == key)
+    }
+
+    /// Iterate over the entries in insertion order.
+    pub fn iter(&self) -> OrderedMapIter<'_, V> {
+        let entry: fn(&Entry<V>) -> (&str, &V) = |(name, value)| (name, value.borrow());
+        self.0.iter().map(entry)
+    }
+
+    /// Iterate over the keys in insertion order.
+    pub fn keys(&self) -> impl Iterator<Item = &str> {
+        self.0.iter().map(|(name, _)| name.as_str())
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+/// The iterator returned from [OrderedMap::iter].
+pub type OrderedMapIter<'a, V> = Map<Iter<'a, Entry<V>>, fn(&'a Entry<V>) -> (&'a str, &'a V)>;
+
+impl<V: ?Sized + ToOwned> Default for OrderedMap<V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<V: ?Sized + ToOwned> Clone for OrderedMap<V>
+where
+    V::Owned: Clone,
+{
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<V: ?Sized + ToOwned> PartialEq for OrderedMap<V>
+where
+    V::Owned: PartialEq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl<V: ?Sized + ToOwned> Debug for OrderedMap<V>
+where
+    V::Owned: Debug,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_map()
+            .entries(self.0.iter().map(|(name, value)| (name, value)))
+            .finish()
+    }
+}
+
+impl<'a, V: ?Sized + ToOwned> IntoIterator for &'a OrderedMap<V> {
+    type Item = (&'a str, &'a V);
+    type IntoIter = OrderedMapIter<'a, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<K, W, V> FromIterator<(K, W)> for OrderedMap<V>
+where
+    K: Into<String>,
+    W: Into<V::Owned>,
+    V: ?Sized + ToOwned,
+{
+    fn from_iter<T: IntoIterator<Item = (K, W)>>(iter: T) -> Self {
+        let mut map = Self::new();
+        for (key, value) in iter {
+            map.insert(key.into(), value.into());
+        }
+        map
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn insertion_order() {
+        let mut map = OrderedMap::<str>::new();
+        map.insert("b".to_string(), "1".to_string());
+        map.insert("a".to_string(), "2".to_string());
+        assert_eq!(
+            map.insert("b".to_string(), "3".to_string()),
+            Some("1".to_string())
+        );
+        assert_eq!(map.iter().collect::<Vec<_>>(), vec![("b", "3"), ("a", "2")]);
+        assert_eq!(map, [("b", "3"), ("a", "2")].into_iter().collect());
+    }
+}
@@ -0,0 +1,98 @@
+use crate::{OrderedMap, OrderedMapIter};
+
+/// The value of a DOM property, set using `HtmlElementBuilder::prop`.
+/// Numeric properties such as the `value` of `<li>`, `<meter>` or `<progress>` are set as numbers,
+/// so the DOM returns the same value and they are not set again on every render.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PropertyValue {
+    Bool(bool),
+    String(String),
+    Number(f64),
+}
+
+impl From<bool> for PropertyValue {
+    fn from(value: bool) -> Self {
+        Self::Bool(value)
+    }
+}
+
+impl From<String> for PropertyValue {
+    fn from(value: String) -> Self {
+        Self::String(value)
+    }
+}
+
+impl From<&str> for PropertyValue {
+    fn from(value: &str) -> Self {
+        Self::String(value.to_string())
+    }
+}
+
+impl From<&String> for PropertyValue {
+    fn from(value: &String) -> Self {
+        Self::String(value.clone())
+    }
+}
+
+impl From<i32> for PropertyValue {
+    fn from(value: i32) -> Self {
+        Self::Number(value.into())
+    }
+}
+
+impl From<u32> for PropertyValue {
+    fn from(value: u32) -> Self {
+        Self::Number(value.into())
+    }
+}
+
+impl From<f64> for PropertyValue {
+    fn from(value: f64) -> Self {
+        Self::Number(value)
+    }
+}
+
+impl PropertyValue {
+    /// The value as an attribute, boolean attributes set to `"false"` are omitted by renderers.
+    pub fn to_attribute(&self) -> String {
+        match self {
+            Self::Bool(value) => value.to_string(),
+            Self::String(value) => value.clone(),
+            Self::Number(value) => value.to_string(),
+        }
+    }
+}
+
+/// The DOM properties of an [crate::HtmlElement] in the order they were first set.
+pub type Properties = OrderedMap<PropertyValue>;
+
+/// The iterator returned from [Properties::iter].
+pub type PropertiesIter<'a> = OrderedMapIter<'a, PropertyValue>;
+
+/// Whether the initial value of a property is rendered as the attribute of the same name
+/// during server-side rendering.
+/// `<textarea>` renders its value as content, `<select>` through the `selected` attribute
+/// of its options and `indeterminate` has no attribute at all.
+pub fn is_reflected_property(tag_name: &str, name: &str) -> bool {
+    match name {
+        "value" => tag_name != "textarea" && tag_name != "select",
+        "checked" | "selected" => true,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reflected_properties() {
+        assert!(is_reflected_property("input", "value"));
+        assert!(!is_reflected_property("textarea", "value"));
+        assert!(is_reflected_property("input", "checked"));
+        assert!(!is_reflected_property("input", "indeterminate"));
+        assert_eq!(PropertyValue::from(false).to_attribute(), "false");
+        assert_eq!(PropertyValue::from(3).to_attribute(), "3");
+        assert_eq!(PropertyValue::from(0.5).to_attribute(), "0.5");
+    }
+}
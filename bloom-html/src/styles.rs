@@ -0,0 +1,80 @@
+use crate::{OrderedMap, OrderedMapIter};
+
+/// The inline styles of an [crate::HtmlElement], set using `HtmlElementBuilder::style`.
+/// Properties use their CSS names, e.G. `background-color`, and keep the order
+/// in which they were first set.
+pub type Styles = OrderedMap<str>;
+
+/// The iterator returned from [Styles::iter].
+pub type StylesIter<'a> = OrderedMapIter<'a, str>;
+
+/// Serialize the styles as the content of a `style` attribute:
+/// `color: red; margin: 0`
+pub(crate) fn to_css(styles: &Styles) -> String {
+    styles
+        .iter()
+        .map(|(name, value)| format!("{}: {}", name, value))
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+/// The class list of an [crate::HtmlElement], set using `HtmlElementBuilder::class`.
+/// Classes keep the order in which they were first added and are only added once.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Classes(Vec<String>);
+
+impl Classes {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a class, returns false if it was already there.
+    pub fn insert(&mut self, name: String) -> bool {
+        if self.contains(&name) {
+            false
+        } else {
+            self.0.push(name);
+            true
+        }
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.0.iter().any(|class| class == name)
+    }
+
+    /// Iterate over the classes in insertion order.
+    pub fn iter(&self) -> impl Iterator<Item = &str> {
+        self.0.iter().map(String::as_str)
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn serialize_styles() {
+        let mut styles = Styles::new();
+        styles.insert("color".to_string(), "red".to_string());
+        styles.insert("margin".to_string(), "0".to_string());
+        styles.insert("color".to_string(), "blue".to_string());
+        assert_eq!(to_css(&styles), "color: blue; margin: 0");
+    }
+
+    #[test]
+    fn deduplicate_classes() {
+        let mut classes = Classes::new();
+        assert!(classes.insert("a".to_string()));
+        assert!(classes.insert("b".to_string()));
+        assert!(!classes.insert("a".to_string()));
+        assert_eq!(classes.iter().collect::<Vec<_>>(), vec!["a", "b"]);
+    }
+}
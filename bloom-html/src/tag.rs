@@ -1,18 +1,759 @@
-//! Some basic html tags
-use crate::{element::HtmlElementBuilder, HtmlNode};
+//! Constructors for all HTML elements with typed setters for their attributes:
+//! ```ignore
+//! a().href("/about").target("_blank").build();
+//! input().r#type(InputType::Checkbox).checked(true).build();
+//! ```
+//! The global attributes such as `id` or `title` can be set on any element.
+//! [attributes] contains the names of the attributes of each element,
+//! bloom-rsx uses them to check attribute names at compile time.
+use crate::{element::HtmlElementBuilder, HtmlElement, PropertyValue};
 
-pub fn div() -> HtmlElementBuilder<&'static str> {
-    HtmlNode::element("div")
+/// Elements that cannot have children and have no closing tag.
+pub const VOID_ELEMENTS: &[&str] = &[
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "keygen", "link", "meta", "param",
+    "source", "track", "wbr",
+];
+
+/// Whether the element cannot have children, e.G. `<br>` or `<input>`.
+pub fn is_void_element(tag_name: &str) -> bool {
+    VOID_ELEMENTS.contains(&tag_name)
+}
+
+macro_rules! attribute_values {
+    ($($(#[$doc:meta])* $name:ident { $($variant:ident = $value:literal),* $(,)? })*) => {
+        $(
+            $(#[$doc])*
+            #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+            pub enum $name {
+                $($variant),*
+            }
+
+            impl $name {
+                pub fn as_str(&self) -> &'static str {
+                    match self {
+                        $(Self::$variant => $value),*
+                    }
+                }
+            }
+        )*
+    };
+}
+
+attribute_values! {
+    /// The `type` of an `<input>`
+    InputType {
+        Button = "button",
+        Checkbox = "checkbox",
+        Color = "color",
+        Date = "date",
+        DatetimeLocal = "datetime-local",
+        Email = "email",
+        File = "file",
+        Hidden = "hidden",
+        Image = "image",
+        Month = "month",
+        Number = "number",
+        Password = "password",
+        Radio = "radio",
+        Range = "range",
+        Reset = "reset",
+        Search = "search",
+        Submit = "submit",
+        Tel = "tel",
+        Text = "text",
+        Time = "time",
+        Url = "url",
+        Week = "week",
+    }
+    /// The `type` of a `<button>`
+    ButtonType {
+        Button = "button",
+        Reset = "reset",
+        Submit = "submit",
+    }
+    /// The `method` of a `<form>`
+    FormMethod {
+        Get = "get",
+        Post = "post",
+        Dialog = "dialog",
+    }
+    /// The text direction set with the global `dir` attribute
+    Dir {
+        Ltr = "ltr",
+        Rtl = "rtl",
+        Auto = "auto",
+    }
+    /// When images and iframes are loaded
+    Loading {
+        Eager = "eager",
+        Lazy = "lazy",
+    }
+    /// How images are decoded
+    Decoding {
+        Sync = "sync",
+        Async = "async",
+        Auto = "auto",
+    }
+    /// The CORS mode of media, scripts and links
+    CrossOrigin {
+        Anonymous = "anonymous",
+        UseCredentials = "use-credentials",
+    }
+    /// How much of a video or audio is loaded ahead
+    Preload {
+        None = "none",
+        Metadata = "metadata",
+        Auto = "auto",
+    }
+}
+
+macro_rules! attribute_name {
+    ($attr:ident) => {
+        stringify!($attr)
+    };
+    ($attr:ident $name:literal) => {
+        $name
+    };
 }
 
-pub fn span() -> HtmlElementBuilder<&'static str> {
-    HtmlNode::element("span")
+/// The setter for one attribute, depending on the kind of its value:
+/// * `text`: any string
+/// * `flag`: a boolean attribute that is present if true
+/// * `boolean`: an attribute with the values `"true"` and `"false"`
+/// * `number` / `integer`: an unsigned / signed number
+/// * `property`: a DOM property, see `HtmlElementBuilder::prop`
+/// * `list`: set via `class` and `style` instead
+/// * `handler`: an inline event handler, prefer the `on_*` methods or set it via `attr`
+/// * one of the enums above
+macro_rules! attribute_setter {
+    ($attr:ident [$($name:literal)?] text) => {
+        #[doc = concat!("set the `", attribute_name!($attr $($name)?), "` attribute")]
+        pub fn $attr<V>(self, value: V) -> Self
+        where
+            V: Into<String>,
+        {
+            self.attr(attribute_name!($attr $($name)?), value)
+        }
+    };
+    ($attr:ident [$($name:literal)?] flag) => {
+        #[doc = concat!("set or remove the boolean `", attribute_name!($attr $($name)?), "` attribute")]
+        pub fn $attr(mut self, value: bool) -> Self {
+            if value {
+                self.attr(attribute_name!($attr $($name)?), "")
+            } else {
+                self.attributes.remove(attribute_name!($attr $($name)?));
+                self
+            }
+        }
+    };
+    ($attr:ident [$($name:literal)?] boolean) => {
+        #[doc = concat!("set the `", attribute_name!($attr $($name)?), "` attribute to `\"true\"` or `\"false\"`")]
+        pub fn $attr(self, value: bool) -> Self {
+            self.attr(attribute_name!($attr $($name)?), value.to_string())
+        }
+    };
+    ($attr:ident [$($name:literal)?] number) => {
+        #[doc = concat!("set the `", attribute_name!($attr $($name)?), "` attribute")]
+        pub fn $attr(self, value: u32) -> Self {
+            self.attr(attribute_name!($attr $($name)?), value.to_string())
+        }
+    };
+    ($attr:ident [$($name:literal)?] integer) => {
+        #[doc = concat!("set the `", attribute_name!($attr $($name)?), "` attribute")]
+        pub fn $attr(self, value: i32) -> Self {
+            self.attr(attribute_name!($attr $($name)?), value.to_string())
+        }
+    };
+    ($attr:ident [$($name:literal)?] property) => {
+        #[doc = concat!("set the `", attribute_name!($attr $($name)?), "` property, see `prop`")]
+        pub fn $attr<V>(self, value: V) -> Self
+        where
+            V: Into<PropertyValue>,
+        {
+            self.prop(attribute_name!($attr $($name)?), value)
+        }
+    };
+    ($attr:ident [$($name:literal)?] list) => {};
+    ($attr:ident [$($name:literal)?] handler) => {};
+    ($attr:ident [$($name:literal)?] $kind:ident) => {
+        #[doc = concat!("set the `", attribute_name!($attr $($name)?), "` attribute")]
+        pub fn $attr(self, value: $kind) -> Self {
+            self.attr(attribute_name!($attr $($name)?), value.as_str())
+        }
+    };
+}
+
+macro_rules! elements {
+    (
+        global { $($global:ident $(= $global_name:literal)?: $global_kind:ident),* $(,)? }
+        $($tag:ident: $Tag:ident { $($attr:ident $(= $name:literal)?: $kind:ident),* $(,)? })*
+    ) => {
+        $(
+            #[doc = concat!("`<", stringify!($tag), ">`")]
+            pub fn $tag() -> HtmlElementBuilder<elements::$Tag> {
+                HtmlElement::new().tag_name(elements::$Tag)
+            }
+        )*
+
+        /// The names of all elements with a constructor in [crate::tag].
+        pub const ELEMENTS: &[&str] = &[$(stringify!($tag)),*];
+
+        /// The tag names of the [HtmlElementBuilder]s returned from the functions in [crate::tag].
+        pub mod elements {
+            use crate::element::TagName;
+
+            $(
+                #[doc = concat!("`<", stringify!($tag), ">`")]
+                #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+                pub struct $Tag;
+
+                impl TagName for $Tag {
+                    fn tag_name(&self) -> &'static str {
+                        stringify!($tag)
+                    }
+                }
+            )*
+        }
+
+        /// The names of the attributes of each element, including the global attributes.
+        /// Hyphens are replaced with underscores, e.G. `meta::http_equiv` is `"http-equiv"`.
+        #[allow(non_upper_case_globals)]
+        pub mod attributes {
+            /// The attributes all elements have
+            pub mod global {
+                $(pub const $global: &str = attribute_name!($global $($global_name)?);)*
+            }
+
+            $(
+                #[doc = concat!("The attributes of `<", stringify!($tag), ">`")]
+                pub mod $tag {
+                    pub use super::global::*;
+
+                    $(pub const $attr: &str = attribute_name!($attr $($name)?);)*
+                }
+            )*
+        }
+
+        impl<T> HtmlElementBuilder<T> {
+            $(attribute_setter!($global [$($global_name)?] $global_kind);)*
+        }
+
+        $(
+            impl HtmlElementBuilder<elements::$Tag> {
+                $(attribute_setter!($attr [$($name)?] $kind);)*
+            }
+        )*
+    };
 }
 
-pub fn button() -> HtmlElementBuilder<&'static str> {
-    HtmlNode::element("button")
+elements! {
+    global {
+        accesskey: text,
+        autocapitalize: text,
+        autofocus: flag,
+        class: list,
+        contenteditable: text,
+        dir: Dir,
+        draggable: boolean,
+        enterkeyhint: text,
+        hidden: flag,
+        id: text,
+        inert: flag,
+        inputmode: text,
+        is: text,
+        itemid: text,
+        itemprop: text,
+        itemref: text,
+        itemscope: flag,
+        itemtype: text,
+        lang: text,
+        nonce: text,
+        part: text,
+        popover: text,
+        role: text,
+        slot: text,
+        spellcheck: boolean,
+        style: list,
+        tabindex: integer,
+        title: text,
+        translate: text,
+        onabort: handler,
+        onanimationcancel: handler,
+        onanimationend: handler,
+        onanimationiteration: handler,
+        onanimationstart: handler,
+        onauxclick: handler,
+        onbeforeinput: handler,
+        onbeforetoggle: handler,
+        onblur: handler,
+        oncancel: handler,
+        oncanplay: handler,
+        oncanplaythrough: handler,
+        onchange: handler,
+        onclick: handler,
+        onclose: handler,
+        oncontextmenu: handler,
+        oncopy: handler,
+        oncuechange: handler,
+        oncut: handler,
+        ondblclick: handler,
+        ondrag: handler,
+        ondragend: handler,
+        ondragenter: handler,
+        ondragleave: handler,
+        ondragover: handler,
+        ondragstart: handler,
+        ondrop: handler,
+        ondurationchange: handler,
+        onemptied: handler,
+        onended: handler,
+        onerror: handler,
+        onfocus: handler,
+        onfocusin: handler,
+        onfocusout: handler,
+        onformdata: handler,
+        ongotpointercapture: handler,
+        oninput: handler,
+        oninvalid: handler,
+        onkeydown: handler,
+        onkeypress: handler,
+        onkeyup: handler,
+        onload: handler,
+        onloadeddata: handler,
+        onloadedmetadata: handler,
+        onloadstart: handler,
+        onlostpointercapture: handler,
+        onmousedown: handler,
+        onmouseenter: handler,
+        onmouseleave: handler,
+        onmousemove: handler,
+        onmouseout: handler,
+        onmouseover: handler,
+        onmouseup: handler,
+        onpaste: handler,
+        onpause: handler,
+        onplay: handler,
+        onplaying: handler,
+        onpointercancel: handler,
+        onpointerdown: handler,
+        onpointerenter: handler,
+        onpointerleave: handler,
+        onpointermove: handler,
+        onpointerout: handler,
+        onpointerover: handler,
+        onpointerup: handler,
+        onprogress: handler,
+        onratechange: handler,
+        onreset: handler,
+        onresize: handler,
+        onscroll: handler,
+        onscrollend: handler,
+        onsecuritypolicyviolation: handler,
+        onseeked: handler,
+        onseeking: handler,
+        onselect: handler,
+        onslotchange: handler,
+        onstalled: handler,
+        onsubmit: handler,
+        onsuspend: handler,
+        ontimeupdate: handler,
+        ontoggle: handler,
+        ontouchcancel: handler,
+        ontouchend: handler,
+        ontouchmove: handler,
+        ontouchstart: handler,
+        ontransitioncancel: handler,
+        ontransitionend: handler,
+        ontransitionrun: handler,
+        ontransitionstart: handler,
+        onvolumechange: handler,
+        onwaiting: handler,
+        onwheel: handler,
+    }
+
+    html: Html { xmlns: text }
+    head: Head {}
+    title: Title {}
+    base: Base { href: text, target: text }
+    link: Link {
+        r#as = "as": text,
+        blocking: text,
+        crossorigin: CrossOrigin,
+        disabled: flag,
+        fetchpriority: text,
+        href: text,
+        hreflang: text,
+        imagesizes: text,
+        imagesrcset: text,
+        integrity: text,
+        media: text,
+        referrerpolicy: text,
+        rel: text,
+        sizes: text,
+        r#type = "type": text,
+    }
+    meta: Meta {
+        charset: text,
+        content: text,
+        http_equiv = "http-equiv": text,
+        media: text,
+        name: text,
+        property: text,
+    }
+    style: Style { blocking: text, media: text }
+    body: Body {
+        onafterprint: handler,
+        onbeforeprint: handler,
+        onbeforeunload: handler,
+        onhashchange: handler,
+        onlanguagechange: handler,
+        onmessage: handler,
+        onmessageerror: handler,
+        onoffline: handler,
+        ononline: handler,
+        onpagehide: handler,
+        onpageshow: handler,
+        onpopstate: handler,
+        onrejectionhandled: handler,
+        onstorage: handler,
+        onunhandledrejection: handler,
+        onunload: handler,
+    }
+
+    article: Article {}
+    section: Section {}
+    nav: Nav {}
+    aside: Aside {}
+    h1: H1 {}
+    h2: H2 {}
+    h3: H3 {}
+    h4: H4 {}
+    h5: H5 {}
+    h6: H6 {}
+    hgroup: Hgroup {}
+    header: Header {}
+    footer: Footer {}
+    address: Address {}
+
+    p: P {}
+    hr: Hr {}
+    pre: Pre {}
+    blockquote: Blockquote { cite: text }
+    ol: Ol { reversed: flag, start: integer, r#type = "type": text }
+    ul: Ul {}
+    menu: Menu {}
+    li: Li { value: property }
+    dl: Dl {}
+    dt: Dt {}
+    dd: Dd {}
+    figure: Figure {}
+    figcaption: Figcaption {}
+    main: Main {}
+    search: Search {}
+    div: Div {}
+
+    a: A {
+        download: text,
+        href: text,
+        hreflang: text,
+        ping: text,
+        referrerpolicy: text,
+        rel: text,
+        target: text,
+        r#type = "type": text,
+    }
+    em: Em {}
+    strong: Strong {}
+    small: Small {}
+    s: S {}
+    cite: Cite {}
+    q: Q { cite: text }
+    dfn: Dfn {}
+    abbr: Abbr {}
+    ruby: Ruby {}
+    rt: Rt {}
+    rp: Rp {}
+    data: Data { value: property }
+    time: Time { datetime: text }
+    code: Code {}
+    var: Var {}
+    samp: Samp {}
+    kbd: Kbd {}
+    sub: Sub {}
+    sup: Sup {}
+    i: I {}
+    b: B {}
+    u: U {}
+    mark: Mark {}
+    bdi: Bdi {}
+    bdo: Bdo {}
+    span: Span {}
+    br: Br {}
+    wbr: Wbr {}
+
+    ins: Ins { cite: text, datetime: text }
+    del: Del { cite: text, datetime: text }
+
+    picture: Picture {}
+    source: Source {
+        height: number,
+        media: text,
+        sizes: text,
+        src: text,
+        srcset: text,
+        r#type = "type": text,
+        width: number,
+    }
+    img: Img {
+        alt: text,
+        crossorigin: CrossOrigin,
+        decoding: Decoding,
+        fetchpriority: text,
+        height: number,
+        ismap: flag,
+        loading: Loading,
+        referrerpolicy: text,
+        sizes: text,
+        src: text,
+        srcset: text,
+        usemap: text,
+        width: number,
+    }
+    iframe: Iframe {
+        allow: text,
+        allowfullscreen: flag,
+        height: number,
+        loading: Loading,
+        name: text,
+        referrerpolicy: text,
+        sandbox: text,
+        src: text,
+        srcdoc: text,
+        width: number,
+    }
+    embed: Embed { height: number, src: text, r#type = "type": text, width: number }
+    object: Object {
+        data: text,
+        form: text,
+        height: number,
+        name: text,
+        r#type = "type": text,
+        width: number,
+    }
+    video: Video {
+        autoplay: flag,
+        controls: flag,
+        crossorigin: CrossOrigin,
+        height: number,
+        r#loop = "loop": flag,
+        muted: flag,
+        playsinline: flag,
+        poster: text,
+        preload: Preload,
+        src: text,
+        width: number,
+    }
+    audio: Audio {
+        autoplay: flag,
+        controls: flag,
+        crossorigin: CrossOrigin,
+        r#loop = "loop": flag,
+        muted: flag,
+        preload: Preload,
+        src: text,
+    }
+    track: Track { default: flag, kind: text, label: text, src: text, srclang: text }
+    map: Map { name: text }
+    area: Area {
+        alt: text,
+        coords: text,
+        download: text,
+        href: text,
+        ping: text,
+        referrerpolicy: text,
+        rel: text,
+        shape: text,
+        target: text,
+    }
+
+    table: Table {}
+    caption: Caption {}
+    colgroup: Colgroup { span: number }
+    col: Col { span: number }
+    tbody: Tbody {}
+    thead: Thead {}
+    tfoot: Tfoot {}
+    tr: Tr {}
+    td: Td { colspan: number, headers: text, rowspan: number }
+    th: Th { abbr: text, colspan: number, headers: text, rowspan: number, scope: text }
+
+    form: Form {
+        accept_charset = "accept-charset": text,
+        action: text,
+        autocomplete: text,
+        enctype: text,
+        method: FormMethod,
+        name: text,
+        novalidate: flag,
+        rel: text,
+        target: text,
+    }
+    label: Label { r#for = "for": text }
+    input: Input {
+        accept: text,
+        alt: text,
+        autocomplete: text,
+        checked: property,
+        dirname: text,
+        disabled: flag,
+        form: text,
+        formaction: text,
+        formenctype: text,
+        formmethod: FormMethod,
+        formnovalidate: flag,
+        formtarget: text,
+        height: number,
+        indeterminate: property,
+        list: text,
+        max: text,
+        maxlength: number,
+        min: text,
+        minlength: number,
+        multiple: flag,
+        name: text,
+        pattern: text,
+        placeholder: text,
+        popovertarget: text,
+        popovertargetaction: text,
+        readonly: flag,
+        required: flag,
+        size: number,
+        src: text,
+        step: text,
+        r#type = "type": InputType,
+        value: property,
+        width: number,
+    }
+    button: Button {
+        disabled: flag,
+        form: text,
+        formaction: text,
+        formenctype: text,
+        formmethod: FormMethod,
+        formnovalidate: flag,
+        formtarget: text,
+        name: text,
+        popovertarget: text,
+        popovertargetaction: text,
+        r#type = "type": ButtonType,
+        value: property,
+    }
+    select: Select {
+        autocomplete: text,
+        disabled: flag,
+        form: text,
+        multiple: flag,
+        name: text,
+        required: flag,
+        size: number,
+        value: property,
+    }
+    datalist: Datalist {}
+    optgroup: Optgroup { disabled: flag, label: text }
+    option: Option { disabled: flag, label: text, selected: property, value: property }
+    textarea: Textarea {
+        autocomplete: text,
+        cols: number,
+        dirname: text,
+        disabled: flag,
+        form: text,
+        maxlength: number,
+        minlength: number,
+        name: text,
+        placeholder: text,
+        readonly: flag,
+        required: flag,
+        rows: number,
+        value: property,
+        wrap: text,
+    }
+    output: Output { r#for = "for": text, form: text, name: text }
+    progress: Progress { max: text, value: property }
+    meter: Meter {
+        high: text,
+        low: text,
+        max: text,
+        min: text,
+        optimum: text,
+        value: property,
+    }
+    fieldset: Fieldset { disabled: flag, form: text, name: text }
+    legend: Legend {}
+
+    details: Details { name: text, open: flag }
+    summary: Summary {}
+    dialog: Dialog { open: flag }
+
+    script: Script {
+        r#async = "async": flag,
+        blocking: text,
+        crossorigin: CrossOrigin,
+        defer: flag,
+        fetchpriority: text,
+        integrity: text,
+        nomodule: flag,
+        referrerpolicy: text,
+        src: text,
+        r#type = "type": text,
+    }
+    noscript: Noscript {}
+    template: Template { shadowrootmode: text }
+    slot: Slot { name: text }
+    canvas: Canvas { height: number, width: number }
 }
 
-pub fn script() -> HtmlElementBuilder<&'static str> {
-    HtmlNode::element("script")
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn typed_attributes() {
+        let element = input()
+            .r#type(InputType::Checkbox)
+            .name("agree")
+            .required(true)
+            .disabled(false)
+            .checked(true)
+            .id("agree")
+            .tabindex(-1)
+            .build();
+
+        assert_eq!(element.tag_name(), "input");
+        assert_eq!(
+            element.attributes().iter().collect::<Vec<_>>(),
+            vec![
+                ("type", "checkbox"),
+                ("name", "agree"),
+                ("required", ""),
+                ("id", "agree"),
+                ("tabindex", "-1"),
+            ]
+        );
+        assert_eq!(
+            element.properties().get("checked"),
+            Some(&PropertyValue::Bool(true))
+        );
+    }
+
+    #[test]
+    fn attribute_names() {
+        assert_eq!(attributes::meta::http_equiv, "http-equiv");
+        assert_eq!(attributes::label::r#for, "for");
+        assert_eq!(attributes::a::title, "title");
+        assert_eq!(attributes::meta::property, "property");
+        assert_eq!(attributes::button::onclick, "onclick");
+        assert_eq!(attributes::body::onpopstate, "onpopstate");
+        assert!(is_void_element("br"));
+        assert!(!is_void_element("div"));
+    }
 }
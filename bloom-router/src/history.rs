@@ -0,0 +1,193 @@
+use std::sync::{Arc, Mutex, OnceLock, Weak};
+
+use bloom_core::use_context;
+
+/// The navigation backend of a [History].
+/// Locations are passed as path, query and hash, e.G. `/users/42?tab=posts`.
+pub trait HistoryBackend: Send + Sync {
+    fn location(&self) -> String;
+    fn push(&self, location: &str);
+    fn replace(&self, location: &str);
+}
+
+/// Keeps the location in memory.
+/// Useful for tests and server-side rendering.
+pub struct MemoryHistory(Mutex<String>);
+
+impl MemoryHistory {
+    pub fn new<L>(location: L) -> Self
+    where
+        L: Into<String>,
+    {
+        Self(Mutex::new(location.into()))
+    }
+}
+
+impl Default for MemoryHistory {
+    fn default() -> Self {
+        Self::new("/")
+    }
+}
+
+impl HistoryBackend for MemoryHistory {
+    fn location(&self) -> String {
+        self.0
+            .lock()
+            .expect("Failed to lock memory history")
+            .clone()
+    }
+
+    fn push(&self, location: &str) {
+        *self.0.lock().expect("Failed to lock memory history") = location.to_string();
+    }
+
+    fn replace(&self, location: &str) {
+        self.push(location);
+    }
+}
+
+pub(crate) type Subscriber = dyn Fn(&str) + Send + Sync;
+
+/// The history the [crate::Router] reads its location from.
+/// Provide it via context to choose a backend.
+/// For server-side rendering, pass the request path:
+/// ```ignore
+/// render_to_stream_with_context(
+///     rsx!(<App />),
+///     TokioSpawner,
+///     vec![Provider::new(History::memory(request.uri().to_string()))],
+///     SerializeOptions::new(),
+/// )
+/// ```
+/// In the browser, use `bloom_client::browser_history()`.
+/// Without a provider, a global [MemoryHistory] starting at `/` is used.
+#[derive(Clone)]
+pub struct History {
+    backend: Arc<dyn HistoryBackend>,
+    subscribers: Arc<Mutex<Vec<Weak<Subscriber>>>>,
+}
+
+impl History {
+    pub fn new<B>(backend: B) -> Self
+    where
+        B: HistoryBackend + 'static,
+    {
+        Self {
+            backend: Arc::new(backend),
+            subscribers: Arc::default(),
+        }
+    }
+
+    /// A history backed by a [MemoryHistory] starting at the given location.
+    pub fn memory<L>(location: L) -> Self
+    where
+        L: Into<String>,
+    {
+        Self::new(MemoryHistory::new(location))
+    }
+
+    pub fn location(&self) -> String {
+        self.backend.location()
+    }
+
+    /// Navigate to a new location, adding an entry to the history.
+    pub fn push(&self, location: &str) {
+        self.backend.push(location);
+        self.notify();
+    }
+
+    /// Navigate to a new location, replacing the current entry.
+    pub fn replace(&self, location: &str) {
+        self.backend.replace(location);
+        self.notify();
+    }
+
+    /// Re-render all routers with the current location of the backend.
+    /// Backends call this when the location was changed from outside, e.G. by the browser's back button.
+    pub fn notify(&self) {
+        let location = self.location();
+        let subscribers = {
+            let mut subscribers = self.subscribers.lock().expect("Failed to lock subscribers");
+            subscribers.retain(|subscriber| subscriber.strong_count() > 0);
+            subscribers
+                .iter()
+                .filter_map(Weak::upgrade)
+                .collect::<Vec<_>>()
+        };
+
+        for subscriber in subscribers {
+            subscriber(&location);
+        }
+    }
+
+    pub(crate) fn subscribe(&self, subscriber: &Arc<Subscriber>) {
+        self.subscribers
+            .lock()
+            .expect("Failed to lock subscribers")
+            .push(Arc::downgrade(subscriber));
+    }
+}
+
+impl Default for History {
+    fn default() -> Self {
+        static GLOBAL: OnceLock<History> = OnceLock::new();
+        GLOBAL
+            .get_or_init(|| History::new(MemoryHistory::default()))
+            .clone()
+    }
+}
+
+/// Navigates the [History] provided via context.
+/// Returned from [use_navigate].
+#[derive(Clone)]
+pub struct Navigate(History);
+
+impl Navigate {
+    pub fn push(&self, location: &str) {
+        self.0.push(location);
+    }
+
+    pub fn replace(&self, location: &str) {
+        self.0.replace(location);
+    }
+}
+
+/// use_navigate returns a handle to navigate programmatically,
+/// e.G. after a form was submitted:
+/// ```ignore
+/// let navigate = use_navigate();
+///
+/// rsx!(<button on_click={move |_| navigate.push("/")}>Home</button>)
+/// ```
+pub fn use_navigate() -> Navigate {
+    Navigate(use_context::<History>().as_ref().clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn notify_subscribers() {
+        let history = History::memory("/");
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let subscriber: Arc<Subscriber> = {
+            let received = received.clone();
+            Arc::new(move |location: &str| {
+                received.lock().unwrap().push(location.to_string());
+            })
+        };
+        history.subscribe(&subscriber);
+
+        history.push("/about");
+        history.replace("/contact?from=about");
+        assert_eq!(history.location(), "/contact?from=about");
+
+        drop(subscriber);
+        history.push("/");
+        assert_eq!(
+            *received.lock().unwrap(),
+            vec!["/about".to_string(), "/contact?from=about".to_string()]
+        );
+    }
+}
@@ -0,0 +1,11 @@
+//! Nested routing for bloom.
+//! The [Router] renders the route matching the location of the [History] provided via context,
+//! so the same route table works for server-side rendering and in the browser.
+mod history;
+mod loader;
+mod path;
+mod router;
+
+pub use history::{use_navigate, History, HistoryBackend, MemoryHistory, Navigate};
+pub use loader::use_loader_data;
+pub use router::{use_route, Route, RouteMatch, Router};
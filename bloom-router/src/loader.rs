@@ -0,0 +1,197 @@
+use std::{
+    any::Any,
+    collections::HashMap,
+    future::Future,
+    pin::Pin,
+    sync::{Arc, Mutex},
+};
+
+use bloom_core::use_context;
+use serde::{de::DeserializeOwned, Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::Value;
+
+use crate::RouteMatch;
+
+pub(crate) type AnyData = Arc<dyn Any + Send + Sync>;
+type LoadFuture<E> = Pin<Box<dyn Future<Output = Result<AnyData, E>> + Send>>;
+type SerializeData = fn(&AnyData) -> Option<Value>;
+type DeserializeData = fn(Value) -> Option<AnyData>;
+
+fn serialize_data<T>(data: &AnyData) -> Option<Value>
+where
+    T: Serialize + 'static,
+{
+    data.downcast_ref::<T>()
+        .and_then(|data| serde_json::to_value(data).ok())
+}
+
+fn deserialize_data<T>(value: Value) -> Option<AnyData>
+where
+    T: DeserializeOwned + Send + Sync + 'static,
+{
+    serde_json::from_value::<T>(value)
+        .ok()
+        .map(|data| Arc::new(data) as AnyData)
+}
+
+/// Loads the data of a route, see [crate::Route::loader].
+pub(crate) struct Loader<E> {
+    load: Arc<dyn Fn(Arc<RouteMatch>) -> LoadFuture<E> + Send + Sync>,
+    serialize: SerializeData,
+    deserialize: DeserializeData,
+}
+
+impl<E> Clone for Loader<E> {
+    fn clone(&self) -> Self {
+        Self {
+            load: self.load.clone(),
+            serialize: self.serialize,
+            deserialize: self.deserialize,
+        }
+    }
+}
+
+impl<E> Loader<E>
+where
+    E: 'static,
+{
+    pub(crate) fn new<F, R, T>(loader: F) -> Self
+    where
+        F: Fn(Arc<RouteMatch>) -> R + Send + Sync + 'static,
+        R: Future<Output = Result<T, E>> + Send + 'static,
+        T: Serialize + DeserializeOwned + Send + Sync + 'static,
+    {
+        Self {
+            load: Arc::new(move |route| {
+                let data = loader(route);
+                Box::pin(async move { data.await.map(|data| Arc::new(data) as AnyData) })
+            }),
+            serialize: serialize_data::<T>,
+            deserialize: deserialize_data::<T>,
+        }
+    }
+
+    pub(crate) fn load(&self, route: Arc<RouteMatch>) -> LoadFuture<E> {
+        (self.load)(route)
+    }
+}
+
+enum Entry {
+    Loaded(AnyData, SerializeData),
+    /// Transferred from server-side rendering, deserialized on first use.
+    Serialized(Value),
+}
+
+/// The loaded data of a router, keyed by route and location.
+/// It is kept in a `use_serialized_state` hook so data loaded during server-side rendering
+/// is reused on hydration instead of being loaded again.
+#[derive(Default)]
+pub(crate) struct LoaderCache(Mutex<HashMap<String, Entry>>);
+
+impl LoaderCache {
+    pub(crate) fn get<E>(&self, key: &str, loader: &Loader<E>) -> Option<AnyData> {
+        let mut entries = self.0.lock().expect("Failed to lock loader cache");
+        let data = match entries.remove(key)? {
+            Entry::Loaded(data, _) => data,
+            Entry::Serialized(value) => (loader.deserialize)(value)?,
+        };
+        entries.insert(
+            key.to_string(),
+            Entry::Loaded(data.clone(), loader.serialize),
+        );
+        Some(data)
+    }
+
+    pub(crate) fn insert<E>(&self, key: String, data: AnyData, loader: &Loader<E>) {
+        self.0
+            .lock()
+            .expect("Failed to lock loader cache")
+            .insert(key, Entry::Loaded(data, loader.serialize));
+    }
+
+    /// Drop the data of routes that are no longer matched.
+    pub(crate) fn retain(&self, keys: &[String]) {
+        self.0
+            .lock()
+            .expect("Failed to lock loader cache")
+            .retain(|key, _| keys.contains(key));
+    }
+}
+
+impl Serialize for LoaderCache {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let entries = self.0.lock().expect("Failed to lock loader cache");
+        serializer.collect_map(entries.iter().filter_map(|(key, entry)| {
+            let value = match entry {
+                Entry::Loaded(data, serialize) => serialize(data)?,
+                Entry::Serialized(value) => value.clone(),
+            };
+            Some((key, value))
+        }))
+    }
+}
+
+impl<'de> Deserialize<'de> for LoaderCache {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let values = HashMap::<String, Value>::deserialize(deserializer)?;
+        Ok(Self(Mutex::new(
+            values
+                .into_iter()
+                .map(|(key, value)| (key, Entry::Serialized(value)))
+                .collect(),
+        )))
+    }
+}
+
+/// The data of the matched routes, from the outermost to the innermost route.
+#[derive(Clone, Default)]
+pub(crate) struct LoaderData(pub(crate) Vec<AnyData>);
+
+/// use_loader_data returns the data loaded for the matched route or one of its parents.
+/// If multiple routes load the same type, the innermost one wins.
+/// ```ignore
+/// Route::new("/users/:id")
+///     .loader(|route| load_user(route.param::<u32>("id")))
+///     .render(|| rsx!(<UserPage />))
+///
+/// // within UserPage
+/// let user = use_loader_data::<User>().expect("user is loaded by the route");
+/// ```
+pub fn use_loader_data<T>() -> Option<Arc<T>>
+where
+    T: Send + Sync + 'static,
+{
+    use_context::<LoaderData>()
+        .0
+        .iter()
+        .rev()
+        .find_map(|data| data.clone().downcast::<T>().ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn transfer_cache() {
+        let loader = Loader::<()>::new(|_| async { Ok(vec![1, 2, 3]) });
+        let cache = LoaderCache::default();
+        cache.insert("/users".to_string(), Arc::new(vec![1, 2, 3]), &loader);
+
+        let json = serde_json::to_string(&cache).unwrap();
+        assert_eq!(json, r#"{"/users":[1,2,3]}"#);
+
+        let restored: LoaderCache = serde_json::from_str(&json).unwrap();
+        let data = restored.get("/users", &loader).unwrap();
+        assert_eq!(data.downcast_ref::<Vec<i32>>(), Some(&vec![1, 2, 3]));
+
+        restored.retain(&[]);
+        assert!(restored.get("/users", &loader).is_none());
+    }
+}
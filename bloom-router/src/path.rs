@@ -0,0 +1,152 @@
+/// One segment of a route pattern such as `/users/:id/*rest`.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Segment {
+    Static(String),
+    /// `:name` matches exactly one segment.
+    Param(String),
+    /// `*name` matches all remaining segments.
+    Rest(String),
+}
+
+pub(crate) fn parse_pattern(pattern: &str) -> Vec<Segment> {
+    pattern
+        .split('/')
+        .filter(|segment| !segment.is_empty())
+        .map(|segment| {
+            if let Some(name) = segment.strip_prefix(':') {
+                Segment::Param(name.to_string())
+            } else if let Some(name) = segment.strip_prefix('*') {
+                Segment::Rest(name.to_string())
+            } else {
+                Segment::Static(segment.to_string())
+            }
+        })
+        .collect()
+}
+
+/// Split a location like `/users/42?tab=posts#top` into its decoded path segments and the query.
+pub(crate) fn split_path(location: &str) -> (Vec<String>, &str) {
+    let location = location.split('#').next().unwrap_or_default();
+    let (path, query) = location.split_once('?').unwrap_or((location, ""));
+    let segments = path
+        .split('/')
+        .filter(|segment| !segment.is_empty())
+        .map(decode)
+        .collect();
+    (segments, query)
+}
+
+/// Match the pattern against the start of the path.
+/// On success, the params are appended and the remaining segments are returned.
+pub(crate) fn match_segments<'a>(
+    pattern: &[Segment],
+    path: &'a [String],
+    params: &mut Vec<(String, String)>,
+) -> Option<&'a [String]> {
+    let mut matched = Vec::new();
+    let mut rest = path;
+    for segment in pattern {
+        match segment {
+            Segment::Rest(name) => {
+                matched.push((name.clone(), rest.join("/")));
+                rest = &rest[rest.len()..];
+                break;
+            }
+            Segment::Static(value) => match rest.split_first() {
+                Some((current, next)) if current == value => rest = next,
+                _ => return None,
+            },
+            Segment::Param(name) => match rest.split_first() {
+                Some((current, next)) => {
+                    matched.push((name.clone(), current.clone()));
+                    rest = next;
+                }
+                None => return None,
+            },
+        }
+    }
+    params.extend(matched);
+    Some(rest)
+}
+
+pub(crate) fn parse_query(query: &str) -> Vec<(String, String)> {
+    query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            (
+                decode(&key.replace('+', " ")),
+                decode(&value.replace('+', " ")),
+            )
+        })
+        .collect()
+}
+
+/// Percent-decode a path segment or query component.
+/// Invalid escapes are kept as they are.
+fn decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        let escaped = (bytes[index] == b'%')
+            .then(|| value.get(index + 1..index + 3))
+            .flatten()
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte);
+                index += 3;
+            }
+            None => {
+                decoded.push(bytes[index]);
+                index += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn match_pattern() {
+        let (path, query) = split_path("/users/42/posts/hello%20world?tab=all#top");
+        assert_eq!(path, vec!["users", "42", "posts", "hello world"]);
+        assert_eq!(query, "tab=all");
+
+        let mut params = Vec::new();
+        let rest = match_segments(&parse_pattern("/users/:id"), &path, &mut params);
+        assert_eq!(rest, Some(&path[2..]));
+        assert_eq!(params, vec![("id".to_string(), "42".to_string())]);
+
+        let mut params = Vec::new();
+        assert_eq!(
+            match_segments(&parse_pattern("/users/:id/comments"), &path, &mut params),
+            None
+        );
+        assert!(params.is_empty());
+
+        let rest = match_segments(&parse_pattern("/users/*rest"), &path, &mut params);
+        assert_eq!(rest, Some(&path[4..]));
+        assert_eq!(
+            params,
+            vec![("rest".to_string(), "42/posts/hello world".to_string())]
+        );
+    }
+
+    #[test]
+    fn query() {
+        assert_eq!(
+            parse_query("q=a+b%26c&page=2&flag"),
+            vec![
+                ("q".to_string(), "a b&c".to_string()),
+                ("page".to_string(), "2".to_string()),
+                ("flag".to_string(), "".to_string()),
+            ]
+        );
+    }
+}
@@ -0,0 +1,564 @@
+use std::{future::Future, str::FromStr, sync::Arc};
+
+use async_trait::async_trait;
+use bloom_core::{
+    spawn, use_context, use_ref_with_default, use_serialized_state, use_state, Component, Element,
+    Provider,
+};
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::{
+    history::{History, Subscriber},
+    loader::{AnyData, Loader, LoaderCache, LoaderData},
+    path::{match_segments, parse_pattern, parse_query, split_path, Segment},
+};
+
+type Render<N, E> = Arc<dyn Fn() -> Element<N, E> + Send + Sync>;
+type Layout<N, E> = Arc<dyn Fn(Element<N, E>) -> Element<N, E> + Send + Sync>;
+/// The loaders of the matched routes, keyed by route, params and query.
+type Loaders<E> = Vec<(String, Loader<E>)>;
+
+/// A route of a [Router].
+/// Patterns consist of static segments, params like `:id` and a trailing rest param like `*path`.
+/// Nested routes are matched relative to their parent and rendered into its layout:
+/// ```ignore
+/// Route::new("/admin")
+///     .layout(|outlet| rsx!(<AdminLayout>{outlet}</AdminLayout>))
+///     .route(Route::new("/").render(|| rsx!(<Dashboard />)))
+///     .route(Route::new("/users/:id").render(|| rsx!(<EditUser />)))
+/// ```
+pub struct Route<N, E>
+where
+    N: From<String>,
+{
+    pattern: String,
+    segments: Vec<Segment>,
+    render: Option<Render<N, E>>,
+    layout: Option<Layout<N, E>>,
+    loader: Option<Loader<E>>,
+    children: Vec<Route<N, E>>,
+}
+
+/// The state of matching a location against the routes.
+struct Matching<E> {
+    params: Vec<(String, String)>,
+    patterns: Vec<String>,
+    loaders: Loaders<E>,
+}
+
+impl<N, E> Route<N, E>
+where
+    N: From<String>,
+    E: 'static,
+{
+    pub fn new<P>(pattern: P) -> Self
+    where
+        P: Into<String>,
+    {
+        let pattern = pattern.into();
+        Self {
+            segments: parse_pattern(&pattern),
+            pattern,
+            render: None,
+            layout: None,
+            loader: None,
+            children: Vec::new(),
+        }
+    }
+
+    /// Render this route if the location matches the pattern exactly.
+    pub fn render<F>(mut self, render: F) -> Self
+    where
+        F: Fn() -> Element<N, E> + Send + Sync + 'static,
+    {
+        self.render = Some(Arc::new(render));
+        self
+    }
+
+    /// Wrap the element of this route or the matched nested route.
+    pub fn layout<F>(mut self, layout: F) -> Self
+    where
+        F: Fn(Element<N, E>) -> Element<N, E> + Send + Sync + 'static,
+    {
+        self.layout = Some(Arc::new(layout));
+        self
+    }
+
+    /// Load data for this route and its nested routes before they are rendered.
+    /// The loaders of all matched routes run concurrently on the spawner
+    /// passed to render_loop or render_stream.
+    /// Components read the result using [crate::use_loader_data].
+    /// Loaders run again once the params or the query change.
+    /// During server-side rendering, the result is serialized for hydration.
+    /// ```ignore
+    /// Route::new("/users/:id")
+    ///     .loader(|route| load_user(route.param::<u32>("id")))
+    ///     .render(|| rsx!(<UserPage />))
+    /// ```
+    pub fn loader<F, R, T>(mut self, loader: F) -> Self
+    where
+        F: Fn(Arc<RouteMatch>) -> R + Send + Sync + 'static,
+        R: Future<Output = Result<T, E>> + Send + 'static,
+        T: Serialize + DeserializeOwned + Send + Sync + 'static,
+    {
+        self.loader = Some(Loader::new(loader));
+        self
+    }
+
+    /// Add a nested route.
+    pub fn route(mut self, route: Route<N, E>) -> Self {
+        self.children.push(route);
+        self
+    }
+
+    fn resolve(&self, path: &[String], matching: &mut Matching<E>) -> Option<Element<N, E>> {
+        let param_count = matching.params.len();
+        let loader_count = matching.loaders.len();
+        let rest = match_segments(&self.segments, path, &mut matching.params)?;
+        matching.patterns.push(self.pattern.clone());
+        let pattern_count = matching.patterns.len();
+
+        let element = self
+            .children
+            .iter()
+            .find_map(|child| child.resolve(rest, matching))
+            .or_else(|| {
+                self.render
+                    .as_ref()
+                    .filter(|_| rest.is_empty())
+                    .map(|render| render())
+            });
+
+        match element {
+            Some(element) => {
+                if let Some(loader) = self.loader.as_ref() {
+                    // The data of a route only depends on the params matched so far
+                    let values = matching.params[..param_count + self.param_count()]
+                        .iter()
+                        .map(|(_, value)| value.as_str())
+                        .collect::<Vec<_>>();
+                    let key = format!(
+                        "{}|{}",
+                        join_patterns(&matching.patterns[..pattern_count]),
+                        values.join("/")
+                    );
+                    matching.loaders.insert(loader_count, (key, loader.clone()));
+                }
+                Some(match self.layout.as_ref() {
+                    Some(layout) => layout(element),
+                    None => element,
+                })
+            }
+            None => {
+                matching.params.truncate(param_count);
+                matching.patterns.pop();
+                None
+            }
+        }
+    }
+
+    fn has_loaders(&self) -> bool {
+        self.loader.is_some() || self.children.iter().any(Route::has_loaders)
+    }
+
+    fn param_count(&self) -> usize {
+        self.segments
+            .iter()
+            .filter(|segment| !matches!(segment, Segment::Static(_)))
+            .count()
+    }
+}
+
+/// The matched route, read using [use_route].
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RouteMatch {
+    location: String,
+    pattern: Option<String>,
+    params: Vec<(String, String)>,
+    query: Vec<(String, String)>,
+}
+
+impl RouteMatch {
+    /// The current location including query and hash.
+    pub fn location(&self) -> &str {
+        &self.location
+    }
+
+    /// The full pattern of the matched route, e.G. `/admin/users/:id`.
+    /// `None` if the fallback was rendered.
+    pub fn pattern(&self) -> Option<&str> {
+        self.pattern.as_deref()
+    }
+
+    /// Parse a param of the matched route:
+    /// ```ignore
+    /// let id = use_route().param::<u32>("id");
+    /// ```
+    pub fn param<T>(&self, name: &str) -> Option<T>
+    where
+        T: FromStr,
+    {
+        find(&self.params, name)
+    }
+
+    /// Parse a query param, e.G. `page` in `/posts?page=2`.
+    pub fn query<T>(&self, name: &str) -> Option<T>
+    where
+        T: FromStr,
+    {
+        find(&self.query, name)
+    }
+}
+
+fn find<T>(values: &[(String, String)], name: &str) -> Option<T>
+where
+    T: FromStr,
+{
+    values
+        .iter()
+        .find(|(key, _)| key == name)
+        .and_then(|(_, value)| value.parse().ok())
+}
+
+/// use_route returns the route matched by the closest [Router].
+pub fn use_route() -> Arc<RouteMatch> {
+    use_context::<RouteMatch>()
+}
+
+/// Renders the first route matching the location of the [History] provided via context.
+/// The router re-renders whenever the history navigates.
+/// ```ignore
+/// Router::new()
+///     .route(Route::new("/").render(|| rsx!(<Home />)))
+///     .route(Route::new("/users/:id").render(|| rsx!(<User />)))
+///     .fallback(|| rsx!(<NotFound />))
+///     .into()
+/// ```
+pub struct Router<N, E>
+where
+    N: From<String>,
+{
+    routes: Vec<Route<N, E>>,
+    fallback: Option<Render<N, E>>,
+}
+
+impl<N, E> Router<N, E>
+where
+    N: From<String>,
+    E: 'static,
+{
+    pub fn new() -> Self {
+        Self {
+            routes: Vec::new(),
+            fallback: None,
+        }
+    }
+
+    pub fn route(mut self, route: Route<N, E>) -> Self {
+        self.routes.push(route);
+        self
+    }
+
+    /// Render this if no route matches the location.
+    pub fn fallback<F>(mut self, fallback: F) -> Self
+    where
+        F: Fn() -> Element<N, E> + Send + Sync + 'static,
+    {
+        self.fallback = Some(Arc::new(fallback));
+        self
+    }
+
+    fn has_loaders(&self) -> bool {
+        self.routes.iter().any(Route::has_loaders)
+    }
+
+    fn resolve(&self, location: &str) -> (RouteMatch, Element<N, E>, Loaders<E>) {
+        let (path, query) = split_path(location);
+        let mut matching = Matching {
+            params: Vec::new(),
+            patterns: Vec::new(),
+            loaders: Vec::new(),
+        };
+        let element = self
+            .routes
+            .iter()
+            .find_map(|route| route.resolve(&path, &mut matching));
+
+        let pattern = element.is_some().then(|| join_patterns(&matching.patterns));
+        let loaders = matching
+            .loaders
+            .into_iter()
+            .map(|(key, loader)| (format!("{}?{}", key, query), loader))
+            .collect();
+        let element = element
+            .or_else(|| self.fallback.as_ref().map(|fallback| fallback()))
+            .unwrap_or_else(|| Element::Fragment(Vec::new()));
+
+        (
+            RouteMatch {
+                location: location.to_string(),
+                pattern,
+                params: matching.params,
+                query: parse_query(query),
+            },
+            element,
+            loaders,
+        )
+    }
+}
+
+impl<N, E> Default for Router<N, E>
+where
+    N: From<String>,
+    E: 'static,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn join_patterns(patterns: &[String]) -> String {
+    let joined = patterns
+        .iter()
+        .flat_map(|pattern| pattern.split('/'))
+        .filter(|segment| !segment.is_empty())
+        .collect::<Vec<_>>()
+        .join("/");
+    format!("/{}", joined)
+}
+
+/// Run the loaders of the matched routes that are not cached yet.
+async fn load<E>(
+    cache: &LoaderCache,
+    route: &Arc<RouteMatch>,
+    loaders: Loaders<E>,
+) -> Result<Vec<AnyData>, E>
+where
+    E: Send + 'static,
+{
+    let mut data = loaders
+        .iter()
+        .map(|(key, loader)| cache.get(key, loader))
+        .collect::<Vec<_>>();
+
+    // Start all missing loaders before awaiting any of them
+    let pending = loaders
+        .iter()
+        .zip(data.iter())
+        .enumerate()
+        .filter(|(_, (_, data))| data.is_none())
+        .map(|(index, ((_, loader), _))| (index, spawn(loader.load(route.clone()))))
+        .collect::<Vec<_>>();
+
+    for (index, load) in pending {
+        let (key, loader) = &loaders[index];
+        let loaded = load.await?;
+        cache.insert(key.clone(), loaded.clone(), loader);
+        data[index] = Some(loaded);
+    }
+
+    cache.retain(&loaders.into_iter().map(|(key, _)| key).collect::<Vec<_>>());
+    Ok(data.into_iter().flatten().collect())
+}
+
+/// Routes can not be compared, so a router re-renders whenever its parent does.
+impl<N, E> PartialEq for Router<N, E>
+where
+    N: From<String>,
+{
+    fn eq(&self, other: &Self) -> bool {
+        std::ptr::eq(self, other)
+    }
+}
+
+#[async_trait]
+impl<N, E> Component for Router<N, E>
+where
+    N: From<String> + Send + Sync + 'static,
+    E: Send + Sync + 'static,
+{
+    type Node = N;
+    type Error = E;
+
+    async fn render(self: Arc<Self>) -> Result<Element<N, E>, E> {
+        let history = use_context::<History>();
+        let location = use_state(|| history.location());
+        use_ref_with_default(|| {
+            let location = location.clone();
+            let subscriber: Arc<Subscriber> = Arc::new(move |next: &str| {
+                let next = next.to_string();
+                location.update(move |_| next);
+            });
+            history.subscribe(&subscriber);
+            subscriber
+        });
+
+        let (route, element, loaders) = self.resolve(&location);
+        let route = Arc::new(route);
+
+        // Whether routes have loaders does not change between renders,
+        // so pages without loaders do not carry an empty cache to the client.
+        let data = if self.has_loaders() {
+            load(&use_serialized_state(LoaderCache::default), &route, loaders).await?
+        } else {
+            Vec::new()
+        };
+
+        Ok(Provider::new(route.as_ref().clone())
+            .children(vec![Provider::new(LoaderData(data)).children(vec![element])]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bloom_core::Provider;
+    use bloom_html::{tag, text, HtmlNode};
+    use bloom_ssr::render_to_string;
+    use futures_util::task::Spawn;
+    use tokio::sync::Barrier;
+
+    use super::*;
+    use crate::use_loader_data;
+
+    #[derive(Clone)]
+    struct TokioSpawner;
+
+    impl Spawn for TokioSpawner {
+        fn spawn_obj(
+            &self,
+            future: futures_util::task::FutureObj<'static, ()>,
+        ) -> Result<(), futures_util::task::SpawnError> {
+            tokio::spawn(future);
+            Ok(())
+        }
+    }
+
+    #[derive(PartialEq)]
+    struct User;
+
+    #[async_trait]
+    impl Component for User {
+        type Node = HtmlNode;
+        type Error = ();
+
+        async fn render(self: Arc<Self>) -> Result<Element<HtmlNode, ()>, ()> {
+            let route = use_route();
+            Ok(text(format!(
+                "{} {} {}",
+                route.pattern().unwrap_or_default(),
+                route.param::<u32>("id").unwrap_or_default(),
+                route.query::<String>("tab").unwrap_or_default(),
+            )))
+        }
+    }
+
+    fn router() -> Router<HtmlNode, ()> {
+        Router::new()
+            .route(Route::new("/").render(|| text("home")))
+            .route(
+                Route::new("/users")
+                    .layout(|outlet| tag("main").build().children(vec![outlet]))
+                    .route(Route::new("/").render(|| text("users")))
+                    .route(Route::new("/:id").render(|| Element::Component(Arc::new(User)))),
+            )
+            .fallback(|| text("not found"))
+    }
+
+    #[test]
+    fn resolve_nested_route() {
+        let router = router();
+
+        let (route, _, _) = router.resolve("/users/42?tab=posts");
+        assert_eq!(route.pattern(), Some("/users/:id"));
+        assert_eq!(route.param::<u32>("id"), Some(42));
+        assert_eq!(route.param::<u32>("missing"), None);
+        assert_eq!(route.query::<String>("tab"), Some("posts".to_string()));
+
+        let (route, _, _) = router.resolve("/users/");
+        assert_eq!(route.pattern(), Some("/users"));
+
+        let (route, _, _) = router.resolve("/users/42/posts");
+        assert_eq!(route.pattern(), None);
+        assert!(route.params.is_empty());
+    }
+
+    #[derive(PartialEq)]
+    struct Profile;
+
+    #[async_trait]
+    impl Component for Profile {
+        type Node = HtmlNode;
+        type Error = ();
+
+        async fn render(self: Arc<Self>) -> Result<Element<HtmlNode, ()>, ()> {
+            let team = use_loader_data::<String>().unwrap_or_default();
+            let id = use_loader_data::<u32>().unwrap_or_default();
+            Ok(text(format!("{} {}", team, id)))
+        }
+    }
+
+    #[tokio::test]
+    async fn load_in_parallel() {
+        // Both loaders wait for each other, so they have to run concurrently
+        let barrier = Arc::new(Barrier::new(2));
+        let team_barrier = barrier.clone();
+        let router: Router<HtmlNode, ()> = Router::new().route(
+            Route::new("/teams/:team")
+                .loader(move |route| {
+                    let barrier = team_barrier.clone();
+                    async move {
+                        barrier.wait().await;
+                        Ok(route.param::<String>("team").unwrap_or_default())
+                    }
+                })
+                .route(
+                    Route::new("/:id")
+                        .loader(move |route| {
+                            let barrier = barrier.clone();
+                            async move {
+                                barrier.wait().await;
+                                Ok(route.param::<u32>("id").unwrap_or_default())
+                            }
+                        })
+                        .render(|| Element::Component(Arc::new(Profile))),
+                ),
+        );
+
+        let (_, _, loaders) = router.resolve("/teams/core/7?tab=posts");
+        let keys = loaders.into_iter().map(|(key, _)| key).collect::<Vec<_>>();
+        assert_eq!(
+            keys,
+            vec![
+                "/teams/:team|core?tab=posts",
+                "/teams/:team/:id|core/7?tab=posts",
+            ]
+        );
+
+        let element = Provider::new(History::memory("/teams/core/7"))
+            .children(vec![Element::Component(Arc::new(router))]);
+
+        let html = render_to_string(element, TokioSpawner).await.unwrap();
+        assert!(html.starts_with("<!--[--><!--[-->core 7<!--]--><!--]--><script"));
+        assert!(html.contains(r#""/teams/:team|core?":"core""#));
+        assert!(html.contains(r#""/teams/:team/:id|core/7?":7"#));
+    }
+
+    #[tokio::test]
+    async fn render_request_path() {
+        let element = Provider::new(History::memory("/users/42?tab=posts"))
+            .children(vec![Element::Component(Arc::new(router()))]);
+
+        assert_eq!(
+            render_to_string(element, TokioSpawner).await,
+            Ok("<!--[--><main><!--[-->/users/:id 42 posts<!--]--></main><!--]-->".to_string())
+        );
+
+        let element = Provider::new(History::memory("/nope"))
+            .children(vec![Element::Component(Arc::new(router()))]);
+
+        assert_eq!(
+            render_to_string(element, TokioSpawner).await,
+            Ok("<!--[-->not found<!--]-->".to_string())
+        );
+    }
+}
@@ -1,13 +1,23 @@
-use proc_macro2::TokenStream;
-use quote::quote;
-use syn::{parse_macro_input, Data, DataStruct, DeriveInput, Expr, ExprPath, Fields};
+use proc_macro2::{Span, TokenStream};
+use quote::{quote, quote_spanned};
+use syn::{
+    parse_macro_input, spanned::Spanned, Data, DataStruct, DeriveInput, Expr, ExprPath, Fields,
+    Ident,
+};
 use syn_rsx::{parse2, Node, NodeName};
 
 /// The core rsx macro.
 /// Transforms
 /// * `<Component prop="value" />` into `Component::new().prop("value").build().into()`
 /// * `<tag attribute="value" on_event={handler} />` into `tag("tag").attr("attribute", "value").on("event", handler).build().into()`
+///   Standard events use the typed methods instead, e.G. `on_click={handler}` becomes `.on_click(handler)`
+///   and modifiers can be appended: `on_submit:prevent={handler}` becomes
+///   `.on_submit(handler).modifiers("submit", EventOptions::new().prevent_default())`
+/// * `<tag class:active={is_active} style:color="red" />` into `.class_if("active", is_active).style("color", "red")`
 /// * `"text"` into `"text".to_string().into()`
+///
+/// Attribute names of HTML elements are checked at compile time against `tag::attributes`,
+/// except for names containing `-` or `:` such as `data-*`, `aria-*` or `hx-post`. Void elements such as `<input>` cannot have children.
 #[proc_macro]
 pub fn rsx(tokens: proc_macro::TokenStream) -> proc_macro::TokenStream {
     let tree = parse2(tokens.into()).expect("Failed to parse RSX");
@@ -59,7 +69,263 @@ fn transform_node(node: Node) -> TokenStream {
     }
 }
 
-fn transform_attributes(attributes: Vec<Node>) -> TokenStream {
+/// The elements with attribute names in `tag::attributes`, the same as `tag::ELEMENTS` of bloom-html.
+/// A proc-macro cannot use bloom-html, so a test keeps both lists in sync.
+const HTML_ELEMENTS: &[&str] = &[
+    "html",
+    "head",
+    "title",
+    "base",
+    "link",
+    "meta",
+    "style",
+    "body",
+    "article",
+    "section",
+    "nav",
+    "aside",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "hgroup",
+    "header",
+    "footer",
+    "address",
+    "p",
+    "hr",
+    "pre",
+    "blockquote",
+    "ol",
+    "ul",
+    "menu",
+    "li",
+    "dl",
+    "dt",
+    "dd",
+    "figure",
+    "figcaption",
+    "main",
+    "search",
+    "div",
+    "a",
+    "em",
+    "strong",
+    "small",
+    "s",
+    "cite",
+    "q",
+    "dfn",
+    "abbr",
+    "ruby",
+    "rt",
+    "rp",
+    "data",
+    "time",
+    "code",
+    "var",
+    "samp",
+    "kbd",
+    "sub",
+    "sup",
+    "i",
+    "b",
+    "u",
+    "mark",
+    "bdi",
+    "bdo",
+    "span",
+    "br",
+    "wbr",
+    "ins",
+    "del",
+    "picture",
+    "source",
+    "img",
+    "iframe",
+    "embed",
+    "object",
+    "video",
+    "audio",
+    "track",
+    "map",
+    "area",
+    "table",
+    "caption",
+    "colgroup",
+    "col",
+    "tbody",
+    "thead",
+    "tfoot",
+    "tr",
+    "td",
+    "th",
+    "form",
+    "label",
+    "input",
+    "button",
+    "select",
+    "datalist",
+    "optgroup",
+    "option",
+    "textarea",
+    "output",
+    "progress",
+    "meter",
+    "fieldset",
+    "legend",
+    "details",
+    "summary",
+    "dialog",
+    "script",
+    "noscript",
+    "template",
+    "slot",
+    "canvas",
+];
+
+/// Elements that cannot have children, the same as `tag::VOID_ELEMENTS` of bloom-html
+const VOID_ELEMENTS: &[&str] = &[
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "keygen", "link", "meta", "param",
+    "source", "track", "wbr",
+];
+
+/// Attributes that are set as DOM properties, so form controls stay in sync with the state.
+const FORM_PROPERTIES: &[&str] = &["value", "checked", "selected", "indeterminate"];
+
+/// Events with a typed handler method on the element builder, e.G. `on_click` passes a `MouseEvent`.
+/// The same as `TYPED_EVENTS` of bloom-html.
+const TYPED_EVENTS: &[&str] = &[
+    "click",
+    "dblclick",
+    "auxclick",
+    "contextmenu",
+    "mousedown",
+    "mouseup",
+    "mousemove",
+    "mouseover",
+    "mouseout",
+    "mouseenter",
+    "mouseleave",
+    "pointerdown",
+    "pointerup",
+    "pointermove",
+    "pointerover",
+    "pointerout",
+    "pointerenter",
+    "pointerleave",
+    "pointercancel",
+    "gotpointercapture",
+    "lostpointercapture",
+    "keydown",
+    "keyup",
+    "keypress",
+    "input",
+    "beforeinput",
+    "focus",
+    "blur",
+    "focusin",
+    "focusout",
+    "wheel",
+    "touchstart",
+    "touchend",
+    "touchmove",
+    "touchcancel",
+    "drag",
+    "dragstart",
+    "dragend",
+    "dragenter",
+    "dragleave",
+    "dragover",
+    "drop",
+    "submit",
+    "copy",
+    "cut",
+    "paste",
+    "compositionstart",
+    "compositionupdate",
+    "compositionend",
+    "animationstart",
+    "animationend",
+    "animationiteration",
+    "animationcancel",
+    "transitionstart",
+    "transitionend",
+    "transitionrun",
+    "transitioncancel",
+    "change",
+    "reset",
+    "invalid",
+    "select",
+    "scroll",
+    "load",
+    "error",
+    "toggle",
+];
+
+/// Modifiers that can be appended to event attributes, e.G. `on_submit:prevent`,
+/// and the methods of `EventOptions` they call.
+const EVENT_MODIFIERS: &[(&str, &str)] = &[
+    ("passive", "passive"),
+    ("capture", "capture"),
+    ("once", "once"),
+    ("prevent", "prevent_default"),
+    ("stop", "stop_propagation"),
+];
+
+/// Split an event attribute such as `submit:prevent` into the event name
+/// and the `EventOptions` methods for its modifiers.
+/// Standard events only take modifiers after the name, so unknown ones do not compile.
+/// Other events keep everything but the trailing modifiers, e.G. `htmx:afterSwap:once`.
+fn split_event_modifiers(key: &str) -> (String, Vec<Ident>) {
+    let modifier = |segment: &str| {
+        EVENT_MODIFIERS
+            .iter()
+            .find(|(modifier, _)| *modifier == segment)
+            .map(|(_, method)| Ident::new(method, Span::call_site()))
+    };
+    let segments = key.split(':').collect::<Vec<_>>();
+    let mut event_len = segments.len();
+    if TYPED_EVENTS.contains(&segments[0]) {
+        if let Some(unknown) = segments[1..]
+            .iter()
+            .find(|segment| modifier(segment).is_none())
+        {
+            panic!("Unknown event modifier \"{}\" on on_{}", unknown, key);
+        }
+        event_len = 1;
+    } else {
+        while event_len > 1 && modifier(segments[event_len - 1]).is_some() {
+            event_len -= 1;
+        }
+    }
+    let modifiers = segments[event_len..]
+        .iter()
+        .filter_map(|segment| modifier(segment))
+        .collect();
+    (segments[..event_len].join(":"), modifiers)
+}
+
+/// The name of an attribute as a path to its constant in `tag::attributes`,
+/// so misspelled or unknown attributes do not compile.
+/// Attributes of custom elements and names containing `-` or `:`,
+/// e.G. `data-*`, `aria-*` or `hx-post`, are passed as strings.
+fn attribute_key(tag: &str, name: &str, span: Span) -> TokenStream {
+    if !HTML_ELEMENTS.contains(&tag) || name.contains('-') || name.contains(':') {
+        return quote! { #name };
+    }
+    let mut attribute =
+        syn::parse_str::<Ident>(name).unwrap_or_else(|_| Ident::new_raw(name, Span::call_site()));
+    attribute.set_span(span);
+    let tag = Ident::new(tag, span);
+    quote_spanned! {span=>
+        tag::attributes::#tag::#attribute
+    }
+}
+
+fn transform_attributes(tag: &str, attributes: Vec<Node>) -> TokenStream {
     let mut attrs = TokenStream::new();
     attributes
         .into_iter()
@@ -72,21 +338,59 @@ fn transform_attributes(attributes: Vec<Node>) -> TokenStream {
                     quote! {
                         .dom_ref(#_value)
                     }
-                } else if name.starts_with("on_") {
-                    let _value: Expr = attribute.value.expect("Callbacks must be functions").into();
-                    let name = name[3..].to_string();
+                } else if let Some(class) = name.strip_prefix("class:") {
+                    match attribute.value {
+                        Some(value) => {
+                            let _value: Expr = value.into();
+                            quote! {
+                                .class_if(#class, #_value)
+                            }
+                        }
+                        None => quote! {
+                            .class(#class)
+                        },
+                    }
+                } else if let Some(property) = name.strip_prefix("style:") {
+                    let _value: Expr = attribute.value.expect("Styles must have a value").into();
                     quote! {
-                        .on(#name, #_value)
+                        .style(#property, #_value)
+                    }
+                } else if let Some(key) = name.strip_prefix("on_") {
+                    let _value: Expr = attribute.value.expect("Callbacks must be functions").into();
+                    let (event, modifiers) = split_event_modifiers(key);
+                    let handler = if TYPED_EVENTS.contains(&event.as_str()) {
+                        let method =
+                            syn::Ident::new(&format!("on_{}", event), attribute.key.span());
+                        quote! {
+                            .#method(#_value)
+                        }
+                    } else {
+                        quote! {
+                            .on(#event, #_value)
+                        }
+                    };
+                    if modifiers.is_empty() {
+                        handler
+                    } else {
+                        quote! {
+                            #handler.modifiers(#event, EventOptions::new()#(.#modifiers())*)
+                        }
                     }
                 } else {
+                    let key = attribute_key(tag, &name, attribute.key.span());
+                    let method = if FORM_PROPERTIES.contains(&name.as_str()) {
+                        quote! { prop }
+                    } else {
+                        quote! { attr }
+                    };
                     if let Some(value) = attribute.value {
                         let _value: Expr = value.into();
                         quote! {
-                            .attr(#name, #_value)
+                            .#method(#key, #_value)
                         }
                     } else {
                         quote! {
-                            .attr(#name, true)
+                            .#method(#key, true)
                         }
                     }
                 }
@@ -151,7 +455,11 @@ fn transform_component(tag: &ExprPath, attributes: Vec<Node>, children: Vec<Node
 }
 
 fn transform_tag(tag: NodeName, attributes: Vec<Node>, children: Vec<Node>) -> TokenStream {
-    let attributes = transform_attributes(attributes);
+    let tag = tag.to_string();
+    let attributes = transform_attributes(&tag, attributes);
+    if !children.is_empty() && VOID_ELEMENTS.contains(&tag.as_str()) {
+        panic!("<{}> is a void element and cannot have children", tag);
+    }
     let children = if children.is_empty() {
         quote! {
             .into()
@@ -162,7 +470,6 @@ fn transform_tag(tag: NodeName, attributes: Vec<Node>, children: Vec<Node>) -> T
             .children(#children)
         }
     };
-    let tag = tag.to_string();
     quote! {
         tag(#tag)#attributes.build()#children
     }
@@ -243,7 +550,7 @@ mod tests {
             .nth(0)
             .unwrap(),
         );
-        assert_eq!(actual.to_string(), "< MyComponent > :: new () . children ({ let mut children = Vec :: with_capacity (1usize) ; children . push (tag (\"div\") . attr (\"id\" , \"child\") . build () . into ()) ; children . into () }) . number_prop (123) . boolean_prop (true) . build () . into ()")
+        assert_eq!(actual.to_string(), "< MyComponent > :: new () . children ({ let mut children = Vec :: with_capacity (1usize) ; children . push (tag (\"div\") . attr (tag :: attributes :: div :: id , \"child\") . build () . into ()) ; children . into () }) . number_prop (123) . boolean_prop (true) . build () . into ()")
     }
 
     #[test]
@@ -259,4 +566,63 @@ mod tests {
         );
         assert_eq!(actual.to_string(), "< MyComponent > :: new () . number_prop (123) . boolean_prop (true) . label (\"test\") . build () . into ()")
     }
+
+    #[test]
+    fn check_attribute_names() {
+        let actual = super::transform_node(
+            syn_rsx::parse2(quote! {
+                <label for="name" data-id="1" hx-post="/x" onclick="go()" my-element:foo="bar"><input value={name} /></label>
+            })
+            .unwrap()
+            .into_iter()
+            .next()
+            .unwrap(),
+        );
+        assert_eq!(actual.to_string(), "tag (\"label\") . attr (tag :: attributes :: label :: r#for , \"name\") . attr (\"data-id\" , \"1\") . attr (\"hx-post\" , \"/x\") . attr (tag :: attributes :: label :: onclick , \"go()\") . attr (\"my-element:foo\" , \"bar\") . build () . children ({ let mut children = Vec :: with_capacity (1usize) ; children . push (tag (\"input\") . prop (tag :: attributes :: input :: value , { name }) . build () . into ()) ; children . into () })")
+    }
+
+    #[test]
+    #[should_panic(expected = "<br> is a void element and cannot have children")]
+    fn reject_void_element_children() {
+        super::transform_node(
+            syn_rsx::parse2(quote! { <br>"text"</br> })
+                .unwrap()
+                .into_iter()
+                .next()
+                .unwrap(),
+        );
+    }
+
+    #[test]
+    fn apply_event_modifiers() {
+        let actual = super::transform_node(
+            syn_rsx::parse2(quote! {
+                <form on_submit:prevent:stop={submit} on_htmx:afterSwap:once={swapped} />
+            })
+            .unwrap()
+            .into_iter()
+            .next()
+            .unwrap(),
+        );
+        assert_eq!(actual.to_string(), "tag (\"form\") . on_submit ({ submit }) . modifiers (\"submit\" , EventOptions :: new () . prevent_default () . stop_propagation ()) . on (\"htmx:afterSwap\" , { swapped }) . modifiers (\"htmx:afterSwap\" , EventOptions :: new () . once ()) . build () . into ()")
+    }
+
+    #[test]
+    fn match_bloom_html() {
+        assert_eq!(super::HTML_ELEMENTS, bloom_html::tag::ELEMENTS);
+        assert_eq!(super::VOID_ELEMENTS, bloom_html::tag::VOID_ELEMENTS);
+        assert_eq!(super::TYPED_EVENTS, bloom_html::TYPED_EVENTS);
+    }
+
+    #[test]
+    #[should_panic(expected = "Unknown event modifier \"prevnt\" on on_submit:prevnt")]
+    fn reject_unknown_modifiers() {
+        super::transform_node(
+            syn_rsx::parse2(quote! { <form on_submit:prevnt={submit} /> })
+                .unwrap()
+                .into_iter()
+                .next()
+                .unwrap(),
+        );
+    }
 }
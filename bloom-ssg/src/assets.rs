@@ -0,0 +1,62 @@
+use std::{collections::HashMap, path::Path, sync::Arc};
+
+use xxhash_rust::xxh3::xxh3_64;
+
+/// The public URLs of the assets of a static site.
+/// Asset file names contain a hash of their content, so they can be cached forever.
+/// Components obtain the URLs via context:
+/// ```ignore
+/// let assets = use_context::<Assets>();
+///
+/// rsx!(<link rel="stylesheet" href={assets.url("style.css")} />)
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct Assets(Arc<HashMap<String, String>>);
+
+impl Assets {
+    pub(crate) fn new(urls: HashMap<String, String>) -> Self {
+        Self(Arc::new(urls))
+    }
+
+    /// The URL of the asset with the given name.
+    /// Unknown assets are returned unchanged.
+    pub fn url(&self, name: &str) -> String {
+        self.0
+            .get(name)
+            .cloned()
+            .unwrap_or_else(|| name.to_string())
+    }
+}
+
+/// Insert a hash of the content into the file name, e.G. `style.css` -> `style.1a2b3c4d5e6f7a8b.css`.
+/// XXH3 yields the same hash on every platform and Rust version, so unchanged assets keep their URL.
+pub(crate) fn hashed_file_name(name: &str, content: &[u8]) -> String {
+    let hash = format!("{:016x}", xxh3_64(content));
+
+    let path = Path::new(name);
+    let file_name = match (
+        path.file_stem().and_then(|stem| stem.to_str()),
+        path.extension().and_then(|extension| extension.to_str()),
+    ) {
+        (Some(stem), Some(extension)) => format!("{}.{}.{}", stem, hash, extension),
+        _ => format!("{}.{}", name, hash),
+    };
+    match path.parent().and_then(|parent| parent.to_str()) {
+        Some(parent) if !parent.is_empty() => format!("{}/{}", parent, file_name),
+        _ => file_name,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stable_file_names() {
+        assert_eq!(
+            hashed_file_name("css/style.css", b"body { color: red; }"),
+            "css/style.058d18713c89cb80.css"
+        );
+        assert_eq!(hashed_file_name("LICENSE", b""), "LICENSE.2d06800538d394c2");
+    }
+}
@@ -0,0 +1,291 @@
+//! Static site generation for bloom-html.
+//! Every route is rendered to an HTML file using bloom-ssr.
+//! ```ignore
+//! StaticSite::new("dist")
+//!     .route("/", || rsx!(<Home />))
+//!     .route("/about", || rsx!(<About />))
+//!     .not_found(|| rsx!(<NotFound />))
+//!     .asset("style.css", "assets/style.css")
+//!     .base_url("https://example.com")
+//!     .build()?;
+//! ```
+use std::{
+    collections::HashMap,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use bloom_core::{Element, Provider};
+use bloom_html::HtmlNode;
+use bloom_ssr::{render_to_stream_with_options, SerializeOptions};
+use futures_util::StreamExt;
+
+mod assets;
+mod sitemap;
+mod spawner;
+
+pub use assets::Assets;
+pub use spawner::LocalSpawner;
+
+use assets::hashed_file_name;
+use sitemap::sitemap;
+use spawner::block_on;
+
+/// The directory within the output directory that assets are written to.
+const ASSETS_DIR: &str = "assets";
+
+#[derive(Debug)]
+pub enum SsgError<E> {
+    Io(io::Error),
+    /// Rendering the page at the given path failed.
+    Render {
+        path: String,
+        error: E,
+    },
+}
+
+impl<E> From<io::Error> for SsgError<E> {
+    fn from(error: io::Error) -> Self {
+        Self::Io(error)
+    }
+}
+
+type Render<E> = Box<dyn Fn() -> Element<HtmlNode, E>>;
+
+/// A static site made up of a route table and assets.
+pub struct StaticSite<E> {
+    out_dir: PathBuf,
+    routes: Vec<(String, Render<E>)>,
+    not_found: Option<Render<E>>,
+    assets: Vec<(String, PathBuf)>,
+    base_url: Option<String>,
+    options: SerializeOptions,
+    serialized_state: bool,
+}
+
+impl<E> StaticSite<E>
+where
+    E: Send + 'static,
+{
+    /// Create a site that is written to the given directory.
+    pub fn new<P>(out_dir: P) -> Self
+    where
+        P: Into<PathBuf>,
+    {
+        Self {
+            out_dir: out_dir.into(),
+            routes: Vec::new(),
+            not_found: None,
+            assets: Vec::new(),
+            base_url: None,
+            options: SerializeOptions::new().doctype(true),
+            serialized_state: false,
+        }
+    }
+
+    /// Add a page.
+    /// `/` is written to `index.html`, `/about` to `about/index.html`
+    /// and paths ending in `.html` are written as they are.
+    pub fn route<P, R>(mut self, path: P, render: R) -> Self
+    where
+        P: Into<String>,
+        R: Fn() -> Element<HtmlNode, E> + 'static,
+    {
+        self.routes.push((path.into(), Box::new(render)));
+        self
+    }
+
+    /// Add a page that is written to `404.html` and left out of the sitemap.
+    pub fn not_found<R>(mut self, render: R) -> Self
+    where
+        R: Fn() -> Element<HtmlNode, E> + 'static,
+    {
+        self.not_found = Some(Box::new(render));
+        self
+    }
+
+    /// Copy a file to the assets directory with a hash of its content in the file name.
+    /// Pages get its URL using [Assets::url] with the given name.
+    pub fn asset<N, P>(mut self, name: N, source: P) -> Self
+    where
+        N: Into<String>,
+        P: Into<PathBuf>,
+    {
+        self.assets.push((name.into(), source.into()));
+        self
+    }
+
+    /// The URL the site is served from, e.G. `https://example.com`.
+    /// A `sitemap.xml` is only written if this is set, as sitemaps require absolute URLs.
+    pub fn base_url<U>(mut self, base_url: U) -> Self
+    where
+        U: Into<String>,
+    {
+        self.base_url = Some(base_url.into());
+        self
+    }
+
+    /// Configure the HTML output, by default only a doctype is added.
+    pub fn options(mut self, options: SerializeOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Embed the values of `use_serialized_state` hooks in each page,
+    /// so the pages can be hydrated using `bloom_client::hydrate`.
+    pub fn serialized_state(mut self, serialized_state: bool) -> Self {
+        self.serialized_state = serialized_state;
+        self
+    }
+
+    /// Render all pages and write the site to the output directory.
+    pub fn build(&self) -> Result<(), SsgError<E>> {
+        fs::create_dir_all(&self.out_dir)?;
+        let assets = self.write_assets()?;
+
+        for (path, render) in self.routes.iter() {
+            let html = self.render_page(path, render(), &assets)?;
+            write(&self.out_dir.join(page_file(path)), html)?;
+        }
+
+        if let Some(render) = self.not_found.as_ref() {
+            let html = self.render_page("/404.html", render(), &assets)?;
+            write(&self.out_dir.join("404.html"), html)?;
+        }
+
+        if let Some(base_url) = self.base_url.as_ref() {
+            let paths = self.routes.iter().map(|(path, _)| path.as_str());
+            write(&self.out_dir.join("sitemap.xml"), sitemap(base_url, paths))?;
+        }
+
+        Ok(())
+    }
+
+    fn write_assets(&self) -> Result<Assets, SsgError<E>> {
+        let mut urls = HashMap::new();
+        for (name, source) in self.assets.iter() {
+            let content = fs::read(source)?;
+            let file_name = hashed_file_name(name.trim_start_matches('/'), &content);
+            write(&self.out_dir.join(ASSETS_DIR).join(&file_name), content)?;
+            urls.insert(name.clone(), format!("/{}/{}", ASSETS_DIR, file_name));
+        }
+        Ok(Assets::new(urls))
+    }
+
+    fn render_page(
+        &self,
+        path: &str,
+        element: Element<HtmlNode, E>,
+        assets: &Assets,
+    ) -> Result<String, SsgError<E>> {
+        let element = Provider::new(assets.clone()).children(vec![element]);
+        block_on(|spawner| async move {
+            let mut stream =
+                render_to_stream_with_options(element, spawner, self.options).in_order();
+            if !self.serialized_state {
+                stream = stream.without_serialized_state();
+            }
+            let mut html = String::new();
+            while let Some(chunk) = stream.next().await {
+                html.push_str(&chunk?);
+            }
+            Ok(html)
+        })
+        .map_err(|error| SsgError::Render {
+            path: path.to_string(),
+            error,
+        })
+    }
+}
+
+/// The file a route is written to, relative to the output directory.
+fn page_file(path: &str) -> PathBuf {
+    let path = path.trim_matches('/');
+    if path.is_empty() {
+        PathBuf::from("index.html")
+    } else if path.ends_with(".html") {
+        PathBuf::from(path)
+    } else {
+        Path::new(path).join("index.html")
+    }
+}
+
+fn write<C>(file: &Path, content: C) -> io::Result<()>
+where
+    C: AsRef<[u8]>,
+{
+    if let Some(parent) = file.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(file, content)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use async_trait::async_trait;
+    use bloom_core::{use_context, Component};
+    use bloom_html::{tag, text};
+
+    use super::*;
+
+    #[derive(PartialEq)]
+    struct Page(&'static str);
+
+    #[async_trait]
+    impl Component for Page {
+        type Node = HtmlNode;
+        type Error = ();
+
+        async fn render(self: Arc<Self>) -> Result<Element<Self::Node, Self::Error>, Self::Error> {
+            let assets = use_context::<Assets>();
+            Ok(tag("html").build().children(vec![
+                tag("head").build().children(vec![tag("link")
+                    .attr("rel", "stylesheet")
+                    .attr("href", assets.url("style.css"))
+                    .build()
+                    .into()]),
+                tag("body").build().children(vec![text(self.0)]),
+            ]))
+        }
+    }
+
+    #[test]
+    fn build_site() {
+        let dir = std::env::temp_dir().join(format!("bloom-ssg-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("style.css"), "body { color: red; }").unwrap();
+
+        StaticSite::new(dir.join("dist"))
+            .route("/", || Element::Component(Arc::new(Page("home"))))
+            .route("/about", || Element::Component(Arc::new(Page("about"))))
+            .not_found(|| text("not found"))
+            .asset("style.css", dir.join("style.css"))
+            .base_url("https://example.com/")
+            .build()
+            .unwrap();
+
+        let css = hashed_file_name("style.css", b"body { color: red; }");
+        assert!(dir.join("dist/assets").join(&css).exists());
+        assert_eq!(
+            fs::read_to_string(dir.join("dist/about/index.html")).unwrap(),
+            format!(
+                "<!DOCTYPE html><!--[--><html><head><link rel=\"stylesheet\" href=\"/assets/{}\"></head><body>about</body></html><!--]-->",
+                css
+            )
+        );
+        assert!(dir.join("dist/index.html").exists());
+        assert_eq!(
+            fs::read_to_string(dir.join("dist/404.html")).unwrap(),
+            "<!DOCTYPE html>not found"
+        );
+        assert_eq!(
+            fs::read_to_string(dir.join("dist/sitemap.xml")).unwrap(),
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">\n  <url><loc>https://example.com/</loc></url>\n  <url><loc>https://example.com/about</loc></url>\n</urlset>\n"
+        );
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,25 @@
+/// Escape text for use in XML.
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+/// Render a sitemap listing the given paths, see https://www.sitemaps.org/protocol.html
+pub(crate) fn sitemap<'a>(base_url: &str, paths: impl Iterator<Item = &'a str>) -> String {
+    let base_url = base_url.trim_end_matches('/');
+    let mut sitemap = String::from(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">\n",
+    );
+    for path in paths {
+        sitemap.push_str(&format!(
+            "  <url><loc>{}{}</loc></url>\n",
+            escape(base_url),
+            escape(path)
+        ));
+    }
+    sitemap.push_str("</urlset>\n");
+    sitemap
+}
@@ -0,0 +1,37 @@
+use async_channel::{unbounded, Sender};
+use futures_executor::LocalPool;
+use futures_util::{
+    future::FutureObj,
+    task::{LocalSpawnExt, Spawn, SpawnError},
+    Future,
+};
+
+/// Runs the tasks spawned while rendering on the thread that builds the site.
+#[derive(Clone)]
+pub struct LocalSpawner(Sender<FutureObj<'static, ()>>);
+
+impl Spawn for LocalSpawner {
+    fn spawn_obj(&self, future: FutureObj<'static, ()>) -> Result<(), SpawnError> {
+        self.0.try_send(future).map_err(|_| SpawnError::shutdown())
+    }
+}
+
+/// Run the future returned from `render` to completion on the current thread.
+pub(crate) fn block_on<R, F>(render: R) -> F::Output
+where
+    R: FnOnce(LocalSpawner) -> F,
+    F: Future,
+{
+    let (sender, receiver) = unbounded::<FutureObj<'static, ()>>();
+    let mut pool = LocalPool::new();
+    let spawner = pool.spawner();
+    let forward = spawner.clone();
+    spawner
+        .spawn_local(async move {
+            while let Ok(future) = receiver.recv().await {
+                let _ = forward.spawn_obj(future);
+            }
+        })
+        .expect("Failed to start local spawner");
+    pool.run_until(render(LocalSpawner(sender)))
+}
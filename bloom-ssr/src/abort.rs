@@ -0,0 +1,40 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    task::Waker,
+};
+
+use futures_util::task::AtomicWaker;
+
+#[derive(Default)]
+struct AbortState {
+    aborted: AtomicBool,
+    waker: AtomicWaker,
+}
+
+/// Stops a [crate::StringStream] from waiting for components that are still rendering,
+/// e.G. once the client disconnected.
+/// Pending suspense boundaries keep their fallback and all open elements are closed,
+/// so the document ends cleanly.
+/// Elements closed this way contain a [bloom_html::CLIENT_RENDER_MARKER] comment,
+/// so bloom-client renders their missing children during hydration
+/// instead of treating them as a mismatch.
+#[derive(Clone, Default)]
+pub struct AbortHandle(Arc<AbortState>);
+
+impl AbortHandle {
+    pub fn abort(&self) {
+        self.0.aborted.store(true, Ordering::Relaxed);
+        self.0.waker.wake();
+    }
+
+    pub fn is_aborted(&self) -> bool {
+        self.0.aborted.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn register(&self, waker: &Waker) {
+        self.0.waker.register(waker);
+    }
+}
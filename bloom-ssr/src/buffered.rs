@@ -0,0 +1,180 @@
+use std::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use bytes::Bytes;
+use futures_util::{Stream, StreamExt};
+
+/// The chunk size used by [BufferedStream] unless configured otherwise.
+pub const DEFAULT_CHUNK_SIZE: usize = 8 * 1024;
+
+/// Coalesces the many small strings of a [crate::StringStream] into [Bytes] chunks.
+/// A chunk is sent once it reaches the configured size or whenever rendering is
+/// waiting for something, e.G. a suspense boundary, so the client receives
+/// everything that is ready as early as possible.
+/// The inner stream is only polled while the consumer asks for more,
+/// so rendering pauses if the client does not keep up.
+/// ```ignore
+/// let stream = render_to_stream(rsx!(<App />), spawner).buffered(16 * 1024);
+/// ```
+pub struct BufferedStream<S, E> {
+    inner: S,
+    buffer: String,
+    chunk_size: usize,
+    /// An error that is returned once the content before it has been sent
+    error: Option<E>,
+    done: bool,
+}
+
+impl<S, E> BufferedStream<S, E>
+where
+    S: Stream<Item = Result<String, E>> + Unpin,
+{
+    pub fn new(inner: S) -> Self {
+        Self {
+            inner,
+            buffer: String::new(),
+            chunk_size: DEFAULT_CHUNK_SIZE,
+            error: None,
+            done: false,
+        }
+    }
+
+    /// Set the size in bytes at which a chunk is sent.
+    pub fn chunk_size(mut self, chunk_size: usize) -> Self {
+        self.chunk_size = chunk_size;
+        self
+    }
+
+    fn flush(&mut self) -> Bytes {
+        let chunk = std::mem::replace(&mut self.buffer, String::with_capacity(self.chunk_size));
+        Bytes::from(chunk)
+    }
+}
+
+// The error is never pinned
+impl<S, E> Unpin for BufferedStream<S, E> where S: Unpin {}
+
+impl<S, E> Stream for BufferedStream<S, E>
+where
+    S: Stream<Item = Result<String, E>> + Unpin,
+{
+    type Item = Result<Bytes, E>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+
+        if let Some(error) = this.error.take() {
+            return Poll::Ready(Some(Err(error)));
+        }
+
+        while !this.done {
+            match this.inner.poll_next_unpin(cx) {
+                Poll::Ready(Some(Ok(chunk))) => {
+                    this.buffer.push_str(&chunk);
+                    if this.buffer.len() >= this.chunk_size {
+                        return Poll::Ready(Some(Ok(this.flush())));
+                    }
+                }
+                Poll::Ready(Some(Err(error))) => {
+                    if this.buffer.is_empty() {
+                        return Poll::Ready(Some(Err(error)));
+                    }
+                    this.error = Some(error);
+                    return Poll::Ready(Some(Ok(this.flush())));
+                }
+                Poll::Ready(None) => this.done = true,
+                Poll::Pending if this.buffer.is_empty() => return Poll::Pending,
+                // Send what is ready while waiting
+                Poll::Pending => return Poll::Ready(Some(Ok(this.flush()))),
+            }
+        }
+
+        if this.buffer.is_empty() {
+            Poll::Ready(None)
+        } else {
+            Poll::Ready(Some(Ok(this.flush())))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use async_channel::Receiver;
+    use async_trait::async_trait;
+    use bloom_core::{Component, Element, Suspense};
+    use bloom_html::{tag::div, text, HtmlNode};
+
+    use crate::{render_to_stream, spawner::TokioSpawner};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn coalesce_chunks() {
+        let element = div().build().children(
+            (0..100)
+                .map(|index| div().build().children(vec![text(index)]))
+                .collect(),
+        );
+
+        let chunks = render_to_stream::<(), TokioSpawner>(element, TokioSpawner)
+            .buffered(1024)
+            .collect::<Vec<_>>()
+            .await;
+
+        assert_eq!(chunks.len(), 2);
+        assert!(chunks[0].as_ref().unwrap().len() >= 1024);
+        assert!(chunks[1]
+            .as_ref()
+            .unwrap()
+            .ends_with(b"<div>99</div></div>"));
+    }
+
+    #[tokio::test]
+    async fn flush_at_suspension() {
+        struct Slow(Receiver<()>);
+
+        impl PartialEq for Slow {
+            fn eq(&self, _other: &Self) -> bool {
+                true
+            }
+        }
+
+        #[async_trait]
+        impl Component for Slow {
+            type Node = HtmlNode;
+            type Error = ();
+
+            async fn render(
+                self: Arc<Self>,
+            ) -> Result<Element<Self::Node, Self::Error>, Self::Error> {
+                let _ = self.0.recv().await;
+                Ok(text("slow"))
+            }
+        }
+
+        let (sender, receiver) = async_channel::bounded(1);
+        let element = div()
+            .build()
+            .children(vec![Suspense::new(|| text("loading"))
+                .children(vec![Element::Component(Arc::new(Slow(receiver)))])]);
+
+        let mut stream = render_to_stream::<(), TokioSpawner>(element, TokioSpawner).buffered(1024);
+
+        assert_eq!(
+            stream.next().await,
+            Some(Ok(Bytes::from(
+                "<div><template id=\"bloom-b0\"></template>loading<!--/bloom-b0--></div>"
+            )))
+        );
+
+        sender.send(()).await.unwrap();
+
+        let rest = stream.next().await.unwrap().unwrap();
+        assert!(rest.starts_with(b"<template id=\"bloom-s0\">"));
+        assert_eq!(stream.next().await, None);
+    }
+}
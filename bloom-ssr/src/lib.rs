@@ -1,7 +1,17 @@
+mod abort;
+mod buffered;
+mod response;
+mod segment;
 mod serializer;
 mod spawner;
 mod stream;
 mod string;
 
-pub use stream::{render_to_stream, StringStream};
-pub use string::render_to_string;
+pub use abort::AbortHandle;
+pub use buffered::{BufferedStream, DEFAULT_CHUNK_SIZE};
+pub use response::ResponseHandle;
+pub use serializer::SerializeOptions;
+pub use stream::{
+    render_to_stream, render_to_stream_with_context, render_to_stream_with_options, StringStream,
+};
+pub use string::{render_to_string, render_to_string_with_options};
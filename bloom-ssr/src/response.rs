@@ -0,0 +1,111 @@
+use std::sync::{Arc, Mutex};
+
+#[derive(Debug)]
+struct ResponseState {
+    status: u16,
+    headers: Vec<(String, String)>,
+    redirect: Option<String>,
+}
+
+impl Default for ResponseState {
+    fn default() -> Self {
+        Self {
+            status: 200,
+            headers: Vec::new(),
+            redirect: None,
+        }
+    }
+}
+
+/// Lets server components influence the response, e.G. to send a 404 page.
+/// render_to_stream_with_context provides it via context,
+/// components obtain it using `use_context::<ResponseHandle>()`.
+/// Outside of render_to_stream_with_context, changes are discarded.
+/// ```ignore
+/// let response = use_context::<ResponseHandle>();
+///
+/// if user.is_none() {
+///     response.redirect("/login");
+/// }
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct ResponseHandle(Arc<Mutex<ResponseState>>);
+
+impl ResponseHandle {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn set_status(&self, status: u16) {
+        self.0.lock().expect("Failed to lock response").status = status;
+    }
+
+    /// The status code, 200 unless set otherwise.
+    pub fn status(&self) -> u16 {
+        self.0.lock().expect("Failed to lock response").status
+    }
+
+    /// Set a header, replacing any header with the same name.
+    /// Header names are case-insensitive.
+    pub fn insert_header<K, V>(&self, key: K, value: V)
+    where
+        K: Into<String>,
+        V: Into<String>,
+    {
+        let (key, value) = (key.into(), value.into());
+        let mut state = self.0.lock().expect("Failed to lock response");
+        state
+            .headers
+            .retain(|(name, _)| !name.eq_ignore_ascii_case(&key));
+        state.headers.push((key, value));
+    }
+
+    /// Add a header without replacing existing ones, e.G. for `Set-Cookie`.
+    pub fn append_header<K, V>(&self, key: K, value: V)
+    where
+        K: Into<String>,
+        V: Into<String>,
+    {
+        self.0
+            .lock()
+            .expect("Failed to lock response")
+            .headers
+            .push((key.into(), value.into()));
+    }
+
+    /// The headers in the order they were set.
+    pub fn headers(&self) -> Vec<(String, String)> {
+        self.0
+            .lock()
+            .expect("Failed to lock response")
+            .headers
+            .clone()
+    }
+
+    /// Redirect to the given location using `302 Found`.
+    /// Use [ResponseHandle::set_status] afterwards for a different status code.
+    pub fn redirect<L>(&self, location: L)
+    where
+        L: Into<String>,
+    {
+        let location = location.into();
+        let mut state = self.0.lock().expect("Failed to lock response");
+        state.status = 302;
+        state
+            .headers
+            .retain(|(name, _)| !name.eq_ignore_ascii_case("location"));
+        state
+            .headers
+            .push(("Location".to_string(), location.clone()));
+        state.redirect = Some(location);
+    }
+
+    /// The redirect target, if any component redirected.
+    pub fn redirect_location(&self) -> Option<String> {
+        self.0
+            .lock()
+            .expect("Failed to lock response")
+            .redirect
+            .clone()
+    }
+}
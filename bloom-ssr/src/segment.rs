@@ -0,0 +1,427 @@
+use std::task::{Context, Poll};
+
+use bloom_core::{NodeStream, StreamItem};
+use bloom_html::{
+    is_void_element, HtmlNode, CLIENT_RENDER_MARKER, FRAGMENT_END_MARKER, FRAGMENT_START_MARKER,
+    TEXT_MARKER,
+};
+use futures_util::StreamExt;
+
+use crate::serializer::{
+    allows_markers, collapse_whitespace, is_preformatted_element,
+    select_value as serialize_select_value, serialize_close_tag, serialize_comment,
+    serialize_open_tag_in, serialize_property_content, serialize_text, SerializeOptions,
+};
+
+/// Moves the children of a resolved suspense boundary in place of its fallback.
+/// The fallback is delimited by an empty template and a closing comment.
+const SWAP_FUNCTION: &str = "function bloomSwap(i){var b=document.getElementById(\"bloom-b\"+i),s=document.getElementById(\"bloom-s\"+i),p=b.parentNode,n=b.nextSibling;while(n&&!(n.nodeType===8&&n.data===\"/bloom-b\"+i)){var x=n.nextSibling;p.removeChild(n);n=x}if(n)p.removeChild(n);p.replaceChild(s.content,b);s.remove()}";
+
+/// A part of the document that is rendered in order.
+/// The root of the document is a segment, and so is every suspense boundary
+/// that was not ready when the stream reached it.
+pub(crate) struct Segment<E> {
+    stack: Vec<Frame<E>>,
+    options: SerializeOptions,
+    /// Render suspense boundaries in place instead of sending their fallback
+    in_order: bool,
+    /// Whether anything has been rendered yet, so the output does not start with a line break
+    started: bool,
+    /// Whether the last node was a text node, which would be merged with a following one by the parser
+    previous_text: bool,
+    /// The tag name of the element closed by the last chunk
+    closed: Option<String>,
+}
+
+struct Frame<E> {
+    /// The tag name of the closest element, used to serialize text correctly
+    parent: Option<String>,
+    /// The tag name if the frame holds the children of an element
+    element: Option<String>,
+    /// The value of the closest `<select>`, to select the matching `<option>`
+    select_value: Option<String>,
+    /// The markup to emit once the stream is done
+    close: Option<String>,
+    /// The depth of the children of this frame, used for pretty printing
+    depth: usize,
+    /// The depth at which to put the closing markup
+    close_depth: usize,
+    /// Whether whitespace is significant within this frame
+    preformatted: bool,
+    has_children: bool,
+    stream: NodeStream<HtmlNode, E>,
+}
+
+/// A suspense boundary whose fallback has been sent.
+/// Boundaries nested inside of it can only be sent after the boundary itself.
+pub(crate) struct PendingSegment<E> {
+    id: usize,
+    segment: Segment<E>,
+    buffer: String,
+    nested: Vec<PendingSegment<E>>,
+}
+
+/// Keeps track of suspense boundaries that are still pending.
+pub(crate) struct Boundaries<E> {
+    pending: Vec<PendingSegment<E>>,
+    next_id: usize,
+    swap_function_sent: bool,
+}
+
+impl<E> Segment<E> {
+    pub(crate) fn new(root: NodeStream<HtmlNode, E>, options: SerializeOptions) -> Self {
+        Self {
+            stack: vec![Frame {
+                parent: None,
+                element: None,
+                select_value: None,
+                close: None,
+                depth: 0,
+                close_depth: 0,
+                preformatted: false,
+                has_children: false,
+                stream: root,
+            }],
+            options,
+            in_order: false,
+            started: false,
+            previous_text: false,
+            closed: None,
+        }
+    }
+
+    /// Render suspense boundaries in place, e.G. if the full output is awaited anyways.
+    pub(crate) fn in_order(mut self) -> Self {
+        self.in_order = true;
+        self
+    }
+
+    /// Start a segment for the children of a suspense boundary within the current frame.
+    fn boundary(&self, children: NodeStream<HtmlNode, E>, frame: &Frame<E>) -> Self {
+        Self {
+            stack: vec![Frame {
+                parent: frame.parent.clone(),
+                element: None,
+                select_value: frame.select_value.clone(),
+                close: None,
+                depth: frame.depth,
+                close_depth: frame.depth,
+                preformatted: frame.preformatted,
+                has_children: false,
+                stream: children,
+            }],
+            options: self.options,
+            in_order: self.in_order,
+            started: self.started,
+            previous_text: self.previous_text,
+            closed: None,
+        }
+    }
+
+    /// The line break and indentation to put before a node when pretty printing.
+    fn indent(&mut self, depth: usize, preformatted: bool) -> String {
+        let started = std::mem::replace(&mut self.started, true);
+        if !started || preformatted || !self.options.is_pretty() {
+            String::new()
+        } else {
+            format!("\n{}", "  ".repeat(depth))
+        }
+    }
+
+    /// Poll the next chunk of HTML.
+    /// Suspense boundaries that are not ready are added to `pending`.
+    pub(crate) fn poll_chunk(
+        &mut self,
+        cx: &mut Context<'_>,
+        pending: &mut Vec<PendingSegment<E>>,
+        next_id: &mut usize,
+    ) -> Poll<Option<Result<String, E>>> {
+        self.closed = None;
+        loop {
+            let frame = match self.stack.last_mut() {
+                Some(frame) => frame,
+                None => return Poll::Ready(None),
+            };
+
+            match frame.stream.poll_next_unpin(cx) {
+                Poll::Ready(Some(Ok(StreamItem::Node(node, children)))) => {
+                    frame.has_children = true;
+                    let depth = frame.depth;
+                    let preformatted = frame.preformatted;
+                    let parent = frame.parent.clone();
+                    let select_value = frame.select_value.clone();
+
+                    match node {
+                        HtmlNode::Element(element) => {
+                            self.previous_text = false;
+                            let indent = self.indent(depth, preformatted);
+                            let tag_name = element.tag_name();
+                            let open_tag = serialize_open_tag_in(
+                                &element,
+                                select_value.as_deref(),
+                                &self.options,
+                            );
+                            // Void elements cannot have children, so they are not rendered at all
+                            if !is_void_element(tag_name) {
+                                self.stack.push(Frame {
+                                    parent: Some(tag_name.to_string()),
+                                    element: Some(tag_name.to_string()),
+                                    select_value: serialize_select_value(&element).or(select_value),
+                                    close: serialize_close_tag(tag_name),
+                                    depth: depth + 1,
+                                    close_depth: depth,
+                                    preformatted: preformatted || is_preformatted_element(tag_name),
+                                    has_children: false,
+                                    stream: children,
+                                });
+                            }
+                            return Poll::Ready(Some(Ok(indent
+                                + &open_tag
+                                + &serialize_property_content(&element, self.options.xhtml))));
+                        }
+                        HtmlNode::Text(text) => {
+                            let text = if self.options.minify && !preformatted {
+                                collapse_whitespace(&text)
+                            } else {
+                                text
+                            };
+                            if self.options.is_pretty() && !preformatted && text.trim().is_empty() {
+                                continue;
+                            }
+                            // Without a marker, the text would be merged with the previous one
+                            // or not be there at all
+                            let previous_text = std::mem::replace(&mut self.previous_text, true);
+                            let marker = if (previous_text || text.is_empty())
+                                && allows_markers(parent.as_deref())
+                            {
+                                serialize_comment(TEXT_MARKER)
+                            } else {
+                                String::new()
+                            };
+                            let indent = self.indent(depth, preformatted);
+                            return Poll::Ready(Some(Ok(indent
+                                + &marker
+                                + &serialize_text(&text, parent.as_deref(), self.options.xhtml))));
+                        }
+                        HtmlNode::Comment(comment) => {
+                            if self.options.minify {
+                                continue;
+                            }
+                            self.previous_text = false;
+                            let indent = self.indent(depth, preformatted);
+                            return Poll::Ready(Some(Ok(
+                                indent + &serialize_comment(comment.text())
+                            )));
+                        }
+                    }
+                }
+                Poll::Ready(Some(Ok(StreamItem::Fragment(children)))) => {
+                    frame.has_children = true;
+                    let (parent, depth, preformatted) =
+                        (frame.parent.clone(), frame.depth, frame.preformatted);
+                    let select_value = frame.select_value.clone();
+                    let markers = allows_markers(parent.as_deref());
+                    self.stack.push(Frame {
+                        parent,
+                        element: None,
+                        select_value,
+                        close: markers.then(|| serialize_comment(FRAGMENT_END_MARKER)),
+                        depth,
+                        close_depth: depth,
+                        preformatted,
+                        has_children: false,
+                        stream: children,
+                    });
+                    if markers {
+                        self.previous_text = false;
+                        let indent = self.indent(depth, preformatted);
+                        return Poll::Ready(Some(Ok(
+                            indent + &serialize_comment(FRAGMENT_START_MARKER)
+                        )));
+                    }
+                }
+                Poll::Ready(Some(Ok(StreamItem::Suspense { fallback, children }))) => {
+                    frame.has_children = true;
+                    if self.in_order {
+                        let frame = self.stack.last().expect("Frame not found");
+                        let boundary = self.boundary(children, frame).stack.remove(0);
+                        self.stack.push(boundary);
+                        continue;
+                    }
+
+                    let frame = self.stack.last().expect("Frame not found");
+                    let mut segment = self.boundary(children, frame);
+                    let (parent, depth, preformatted) =
+                        (frame.parent.clone(), frame.depth, frame.preformatted);
+                    let select_value = frame.select_value.clone();
+
+                    // Try to render the boundary right away and only send the fallback if it is not ready
+                    let mut buffer = String::new();
+                    let mut nested = Vec::new();
+                    match segment.poll_into(cx, &mut buffer, &mut nested, next_id) {
+                        Poll::Ready(Ok(())) => {
+                            pending.append(&mut nested);
+                            self.started = segment.started;
+                            self.previous_text = segment.previous_text;
+                            return Poll::Ready(Some(Ok(buffer)));
+                        }
+                        Poll::Ready(Err(error)) => return Poll::Ready(Some(Err(error))),
+                        Poll::Pending => {
+                            let id = *next_id;
+                            *next_id += 1;
+                            pending.push(PendingSegment {
+                                id,
+                                segment,
+                                buffer,
+                                nested,
+                            });
+                            self.previous_text = false;
+                            let indent = self.indent(depth, preformatted);
+                            self.stack.push(Frame {
+                                parent,
+                                element: None,
+                                select_value,
+                                close: Some(format!("<!--/bloom-b{}-->", id)),
+                                depth,
+                                close_depth: depth,
+                                preformatted,
+                                has_children: true,
+                                stream: fallback,
+                            });
+                            return Poll::Ready(Some(Ok(format!(
+                                "{}<template id=\"bloom-b{}\"></template>",
+                                indent, id
+                            ))));
+                        }
+                    }
+                }
+                Poll::Ready(Some(Err(error))) => return Poll::Ready(Some(Err(error))),
+                Poll::Ready(None) => {
+                    if let Some(close) = self.close_frame(false) {
+                        return Poll::Ready(Some(Ok(close)));
+                    }
+                }
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+
+    /// Pop the innermost frame and return its closing markup, if any.
+    /// Elements that are closed early are marked, so the client renders their missing children.
+    fn close_frame(&mut self, early: bool) -> Option<String> {
+        let frame = self.stack.pop()?;
+        let close = frame.close?;
+        self.previous_text = false;
+        let marker = match frame.element.as_deref() {
+            Some(element) if early && allows_markers(Some(element)) => {
+                serialize_comment(CLIENT_RENDER_MARKER)
+            }
+            _ => String::new(),
+        };
+        self.closed = frame.element;
+        let indent = if frame.has_children {
+            self.indent(frame.close_depth, frame.preformatted)
+        } else {
+            String::new()
+        };
+        Some(indent + &marker + &close)
+    }
+
+    /// Close the innermost open element or fragment without waiting for the rest of it.
+    /// Returns `None` once everything is closed.
+    pub(crate) fn close_next(&mut self) -> Option<String> {
+        self.closed = None;
+        while !self.stack.is_empty() {
+            if let Some(close) = self.close_frame(true) {
+                return Some(close);
+            }
+        }
+        None
+    }
+
+    /// The tag name of the element closed by the last chunk, e.G. to find the end of the `<head>`.
+    pub(crate) fn closed_element(&self) -> Option<&str> {
+        self.closed.as_deref()
+    }
+
+    /// Render as much of the segment as possible into the buffer.
+    pub(crate) fn poll_into(
+        &mut self,
+        cx: &mut Context<'_>,
+        buffer: &mut String,
+        pending: &mut Vec<PendingSegment<E>>,
+        next_id: &mut usize,
+    ) -> Poll<Result<(), E>> {
+        loop {
+            match self.poll_chunk(cx, pending, next_id) {
+                Poll::Ready(Some(Ok(chunk))) => buffer.push_str(&chunk),
+                Poll::Ready(Some(Err(error))) => return Poll::Ready(Err(error)),
+                Poll::Ready(None) => return Poll::Ready(Ok(())),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
+
+impl<E> Boundaries<E> {
+    pub(crate) fn new() -> Self {
+        Self {
+            pending: Vec::new(),
+            next_id: 0,
+            swap_function_sent: false,
+        }
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    /// Give up on all pending boundaries, their fallback stays in place.
+    pub(crate) fn clear(&mut self) {
+        self.pending.clear();
+    }
+
+    /// Poll the next chunk of the given segment, registering its pending boundaries.
+    pub(crate) fn poll_segment(
+        &mut self,
+        segment: &mut Segment<E>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<String, E>>> {
+        segment.poll_chunk(cx, &mut self.pending, &mut self.next_id)
+    }
+
+    /// Drive all pending boundaries and return the chunk for the first one that resolved.
+    pub(crate) fn poll_resolved(&mut self, cx: &mut Context<'_>) -> Poll<Result<String, E>> {
+        for index in 0..self.pending.len() {
+            let boundary = &mut self.pending[index];
+            match boundary.segment.poll_into(
+                cx,
+                &mut boundary.buffer,
+                &mut boundary.nested,
+                &mut self.next_id,
+            ) {
+                Poll::Ready(Ok(())) => {
+                    let boundary = self.pending.remove(index);
+                    self.pending.extend(boundary.nested);
+                    return Poll::Ready(Ok(self.resolved_chunk(boundary.id, boundary.buffer)));
+                }
+                Poll::Ready(Err(error)) => return Poll::Ready(Err(error)),
+                Poll::Pending => {}
+            }
+        }
+        Poll::Pending
+    }
+
+    fn resolved_chunk(&mut self, id: usize, content: String) -> String {
+        let swap_function = if self.swap_function_sent {
+            ""
+        } else {
+            self.swap_function_sent = true;
+            SWAP_FUNCTION
+        };
+        format!(
+            "<template id=\"bloom-s{}\">{}</template><script>{}bloomSwap({})</script>",
+            id, content, swap_function, id
+        )
+    }
+}
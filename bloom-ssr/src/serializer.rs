@@ -1,12 +1,423 @@
-use bloom_html::HtmlElement;
+//! HTML5 serialization of bloom-html nodes, following
+//! https://html.spec.whatwg.org/multipage/parsing.html#serialising-html-fragments
+
+use bloom_core::SerializedState;
+use bloom_html::{
+    is_boolean_attribute, is_reflected_property, is_rendered_attribute, is_void_element, HeadTag,
+    HtmlElement, PropertyValue, SERIALIZED_STATE_ID,
+};
+
+/// Serialization options for render_to_string and render_to_stream.
+/// ```ignore
+/// render_to_string_with_options(rsx!(<App />), spawner, SerializeOptions::new().doctype(true).pretty(true))
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct SerializeOptions {
+    pub(crate) pretty: bool,
+    pub(crate) minify: bool,
+    pub(crate) doctype: bool,
+    pub(crate) xhtml: bool,
+}
+
+impl SerializeOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Put every node on its own line and indent it according to its depth.
+    /// The content of preformatted elements like `<pre>` is left untouched.
+    /// Useful for debugging and snapshot tests but it changes whitespace between inline elements.
+    pub fn pretty(mut self, pretty: bool) -> Self {
+        self.pretty = pretty;
+        self
+    }
+
+    /// Collapse whitespace in text and omit comments.
+    /// Takes precedence over pretty.
+    pub fn minify(mut self, minify: bool) -> Self {
+        self.minify = minify;
+        self
+    }
+
+    /// Start the document with `<!DOCTYPE html>`.
+    pub fn doctype(mut self, doctype: bool) -> Self {
+        self.doctype = doctype;
+        self
+    }
+
+    /// Produce XHTML-compatible markup:
+    /// void elements are self-closed and boolean attributes get an explicit value.
+    pub fn xhtml(mut self, xhtml: bool) -> Self {
+        self.xhtml = xhtml;
+        self
+    }
+
+    pub(crate) fn is_pretty(&self) -> bool {
+        self.pretty && !self.minify
+    }
+}
+
+/// Elements whose text content is not parsed as HTML.
+const RAW_TEXT_ELEMENTS: &[&str] = &["script", "style"];
+
+/// Elements in which whitespace is significant.
+const PREFORMATTED_ELEMENTS: &[&str] = &["pre", "textarea", "script", "style"];
+
+pub(crate) fn is_preformatted_element(tag_name: &str) -> bool {
+    PREFORMATTED_ELEMENTS.contains(&tag_name)
+}
+
+/// Comments cannot be used to mark hydration boundaries in elements whose content is text only.
+pub(crate) fn allows_markers(parent: Option<&str>) -> bool {
+    !matches!(parent, Some("script" | "style" | "textarea" | "title"))
+}
+
+/// Attribute names containing any of these characters would break out of the tag.
+fn is_valid_attribute_name(name: &str) -> bool {
+    !name.is_empty()
+        && !name.chars().any(|c| {
+            c.is_whitespace() || c.is_control() || matches!(c, '"' | '\'' | '>' | '/' | '=' | '<')
+        })
+}
+
+/// XML does not know the `&nbsp;` entity.
+fn nbsp(xhtml: bool) -> &'static str {
+    if xhtml {
+        "&#160;"
+    } else {
+        "&nbsp;"
+    }
+}
+
+pub(crate) fn escape_text(text: &str, xhtml: bool) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '\u{a0}' => escaped.push_str(nbsp(xhtml)),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+pub(crate) fn escape_attribute(value: &str, xhtml: bool) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '\u{a0}' => escaped.push_str(nbsp(xhtml)),
+            '"' => escaped.push_str("&quot;"),
+            '<' if xhtml => escaped.push_str("&lt;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn serialize_attribute(name: &str, value: &str, options: &SerializeOptions) -> String {
+    if !is_valid_attribute_name(name) || !is_rendered_attribute(name, value) {
+        return String::new();
+    }
+    if is_boolean_attribute(name) {
+        return if options.xhtml {
+            format!(" {}=\"{}\"", name, name)
+        } else {
+            format!(" {}", name)
+        };
+    }
+    format!(" {}=\"{}\"", name, escape_attribute(value, options.xhtml))
+}
+
+/// Serialize the opening tag of an element including its attributes.
+/// Properties like `value` or `checked` are rendered as the attributes that set their initial state,
+/// unless the attribute was set explicitly.
+/// In XHTML mode, void elements are self-closed.
+pub(crate) fn serialize_open_tag(element: &HtmlElement, options: &SerializeOptions) -> String {
+    serialize_open_tag_in(element, None, options)
+}
+
+/// The value of a `<select>` set as a property, which is rendered as the `selected` attribute
+/// of the matching `<option>`.
+pub(crate) fn select_value(element: &HtmlElement) -> Option<String> {
+    (element.tag_name() == "select")
+        .then(|| element.properties().get("value"))
+        .flatten()
+        .map(PropertyValue::to_attribute)
+}
+
+/// Like [serialize_open_tag] but within a `<select>` with the given value,
+/// an `<option>` with the same value is selected.
+/// Options need a `value` to be matched, their text content is not known yet.
+pub(crate) fn serialize_open_tag_in(
+    element: &HtmlElement,
+    select_value: Option<&str>,
+    options: &SerializeOptions,
+) -> String {
+    let properties = element
+        .properties()
+        .iter()
+        .filter(|(name, _)| {
+            is_reflected_property(element.tag_name(), name)
+                && !element.attributes().contains_key(name)
+        })
+        .map(|(name, value)| serialize_attribute(name, &value.to_attribute(), options));
+
+    let option_value = element.attribute("value").map(str::to_string).or_else(|| {
+        element
+            .properties()
+            .get("value")
+            .map(PropertyValue::to_attribute)
+    });
+    let selected = element.tag_name() == "option"
+        && select_value.is_some()
+        && option_value.as_deref() == select_value
+        && !element.attributes().contains_key("selected")
+        && !element.properties().contains_key("selected");
+    let properties =
+        properties.chain(selected.then(|| serialize_attribute("selected", "", options)));
 
-pub(crate) fn serialize_node_open(node: &HtmlElement) -> String {
     format!(
-        "<{}{}>",
-        node.tag_name(),
-        node.attributes()
-            .iter()
-            .map(|(key, value)| format!(" {}=\"{}\"", key, value))
-            .collect::<String>()
+        "<{}{}{}>",
+        element.tag_name(),
+        element
+            .rendered_attributes()
+            .map(|(name, value)| serialize_attribute(name, &value, options))
+            .chain(properties)
+            .collect::<String>(),
+        if options.xhtml && is_void_element(element.tag_name()) {
+            " /"
+        } else {
+            ""
+        }
     )
 }
+
+/// The initial content of a `<textarea>` is set through its value property.
+pub(crate) fn serialize_property_content(element: &HtmlElement, xhtml: bool) -> String {
+    match element.properties().get("value") {
+        Some(PropertyValue::String(value)) if element.tag_name() == "textarea" => {
+            serialize_text(value, Some("textarea"), xhtml)
+        }
+        _ => String::new(),
+    }
+}
+
+/// Serialize the closing tag of an element.
+/// Void elements are not closed.
+pub(crate) fn serialize_close_tag(tag_name: &str) -> Option<String> {
+    if is_void_element(tag_name) {
+        None
+    } else {
+        Some(format!("</{}>", tag_name))
+    }
+}
+
+/// Serialize a text node depending on its parent element.
+/// The content of raw text elements is not escaped, but it is not allowed
+/// to close the element prematurely.
+/// Escapable raw text elements (`<textarea>`, `<title>`) are escaped like any other text.
+pub(crate) fn serialize_text(text: &str, parent: Option<&str>, xhtml: bool) -> String {
+    match parent {
+        Some(parent) if RAW_TEXT_ELEMENTS.contains(&parent) => {
+            let closing_tag = format!("</{}", parent);
+            let mut output = String::with_capacity(text.len());
+            let mut rest = text;
+            while let Some(index) = rest.to_ascii_lowercase().find(&closing_tag) {
+                output.push_str(&rest[..index]);
+                output.push_str("<\\/");
+                rest = &rest[index + 2..];
+            }
+            output.push_str(rest);
+            output
+        }
+        _ => escape_text(text, xhtml),
+    }
+}
+
+/// Collapse runs of whitespace into a single space.
+pub(crate) fn collapse_whitespace(text: &str) -> String {
+    let mut collapsed = String::with_capacity(text.len());
+    let mut whitespace = false;
+    for c in text.chars() {
+        if c.is_ascii_whitespace() {
+            if !whitespace {
+                collapsed.push(' ');
+            }
+            whitespace = true;
+        } else {
+            collapsed.push(c);
+            whitespace = false;
+        }
+    }
+    collapsed
+}
+
+/// Serialize a comment.
+/// The text is sanitized so it cannot end the comment prematurely.
+pub(crate) fn serialize_comment(text: &str) -> String {
+    let mut text = text.replace("--", "- -");
+    while text.contains("--") {
+        text = text.replace("--", "- -");
+    }
+    if text.starts_with('>') || text.starts_with("->") {
+        text.insert(0, ' ');
+    }
+    if text.ends_with("<!-") || text.ends_with('-') {
+        text.push(' ');
+    }
+    format!("<!--{}-->", text)
+}
+
+/// Serialize the tags collected from `use_head`, each prefixed with the given indentation.
+pub(crate) fn serialize_head(tags: &[HeadTag], indent: &str, options: &SerializeOptions) -> String {
+    let mut output = String::new();
+    for tag in tags {
+        let element = tag.element();
+        output.push_str(indent);
+        output.push_str(&serialize_open_tag(element, options));
+        if let Some(text) = tag.text_content() {
+            output.push_str(&serialize_text(
+                text,
+                Some(element.tag_name()),
+                options.xhtml,
+            ));
+        }
+        if let Some(close) = serialize_close_tag(element.tag_name()) {
+            output.push_str(&close);
+        }
+    }
+    output
+}
+
+/// Moves the tags in the late head template into the document head,
+/// replacing the tags with the same key.
+const LATE_HEAD_FUNCTION: &str = "(function(){var t=document.getElementById(\"bloom-head\"),h=document.head;Array.prototype.slice.call(t.content.children).forEach(function(e){var o=h.querySelector(\"[data-bloom-head=\\\"\"+CSS.escape(e.getAttribute(\"data-bloom-head\"))+\"\\\"]\");o?h.replaceChild(e,o):h.appendChild(e)});t.remove()})()";
+
+/// Serialize head tags that were set after `</head>` has been sent,
+/// together with the script that moves them into the head.
+pub(crate) fn serialize_late_head(tags: &[HeadTag], options: &SerializeOptions) -> String {
+    format!(
+        "<template id=\"bloom-head\">{}</template><script>{}</script>",
+        serialize_head(tags, "", options),
+        LATE_HEAD_FUNCTION
+    )
+}
+
+/// Embeds the serialized state as a JSON script.
+/// `<` is escaped so the payload cannot close the script element.
+pub(crate) fn serialize_state(state: &SerializedState) -> String {
+    format!(
+        "<script type=\"application/json\" id=\"{}\">{}</script>",
+        SERIALIZED_STATE_ID,
+        state.to_json().replace('<', "\\u003c")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use bloom_html::{tag, tag::div};
+
+    use super::*;
+
+    #[test]
+    fn escape() {
+        let options = SerializeOptions::new();
+        assert_eq!(
+            serialize_text("<script>alert(\"&\")</script>", Some("div"), false),
+            "&lt;script&gt;alert(\"&amp;\")&lt;/script&gt;"
+        );
+        assert_eq!(
+            serialize_open_tag(&div().attr("title", "\"><script>").build(), &options),
+            "<div title=\"&quot;><script>\">"
+        );
+        assert_eq!(
+            serialize_open_tag(&div().attr("\"><script>", "foo").build(), &options),
+            "<div>"
+        );
+    }
+
+    #[test]
+    fn void_and_boolean() {
+        let options = SerializeOptions::new();
+        assert_eq!(serialize_close_tag("br"), None);
+        assert_eq!(serialize_close_tag("div"), Some("</div>".to_string()));
+        assert_eq!(
+            serialize_open_tag(&tag("input").attr("disabled", "").build(), &options),
+            "<input disabled>"
+        );
+        assert_eq!(
+            serialize_open_tag(&tag("input").attr("disabled", "false").build(), &options),
+            "<input>"
+        );
+        assert_eq!(
+            serialize_open_tag(&div().attr("hidden", "until-found").build(), &options),
+            "<div hidden=\"until-found\">"
+        );
+    }
+
+    #[test]
+    fn xhtml() {
+        let options = SerializeOptions::new().xhtml(true);
+        assert_eq!(
+            serialize_open_tag(
+                &tag("input")
+                    .attr("disabled", "")
+                    .attr("value", "a\u{a0}<b")
+                    .build(),
+                &options
+            ),
+            "<input disabled=\"disabled\" value=\"a&#160;&lt;b\" />"
+        );
+    }
+
+    #[test]
+    fn classes_and_styles() {
+        let options = SerializeOptions::new();
+        assert_eq!(
+            serialize_open_tag(
+                &div()
+                    .attr("class", "card")
+                    .class("active")
+                    .class_if("hidden", false)
+                    .style("color", "red")
+                    .style("opacity", 0.5)
+                    .build(),
+                &options
+            ),
+            "<div class=\"card active\" style=\"color: red; opacity: 0.5\">"
+        );
+        assert_eq!(
+            serialize_open_tag(
+                &div()
+                    .attr("style", "margin: 0;")
+                    .style("color", "red")
+                    .build(),
+                &options
+            ),
+            "<div style=\"margin: 0; color: red\">"
+        );
+    }
+
+    #[test]
+    fn raw_text() {
+        assert_eq!(
+            serialize_text("if (a < b) { x = '</SCRIPT>' }", Some("script"), false),
+            "if (a < b) { x = '<\\/SCRIPT>' }"
+        );
+        assert_eq!(
+            serialize_text("</textarea>", Some("textarea"), false),
+            "&lt;/textarea&gt;"
+        );
+    }
+
+    #[test]
+    fn comment() {
+        assert_eq!(serialize_comment("foo"), "<!--foo-->");
+        assert_eq!(serialize_comment("a-->b"), "<!--a- ->b-->");
+        assert_eq!(serialize_comment("---"), "<!--- - - -->");
+        assert_eq!(serialize_comment(">x"), "<!-- >x-->");
+    }
+}
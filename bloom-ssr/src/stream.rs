@@ -1,15 +1,52 @@
-use std::task::Poll;
+use std::{
+    collections::VecDeque,
+    future::poll_fn,
+    pin::Pin,
+    task::{Context, Poll},
+};
 
-use bloom_core::{render_stream, Element, NodeStream};
-use bloom_html::HtmlNode;
-use futures_util::{task::Spawn, Stream, StreamExt};
+use bloom_core::{render_stream, Element, NodeStream, Provider, SerializedState};
+use bloom_html::{Head, HeadTag, HtmlNode};
+use futures_util::{task::Spawn, Future, FutureExt, Stream};
 
-use crate::serializer::serialize_node_open;
+use crate::{
+    abort::AbortHandle,
+    buffered::BufferedStream,
+    response::ResponseHandle,
+    segment::{Boundaries, Segment},
+    serializer::{serialize_head, serialize_late_head, serialize_state, SerializeOptions},
+};
 
 /// The stream type returned by the render_to_stream API.
 /// This stream will yield strings representing the HTML output of the rendered component.
+/// Suspense boundaries that are not ready when the stream reaches them
+/// are sent out of order: Their fallback is sent in place and the resolved children
+/// follow later together with a small script that moves them into place.
+/// Tags set via `use_head` are inserted before `</head>`,
+/// so the output starting at `</head>` is held back until everything outside of
+/// pending suspense boundaries is rendered.
+/// Tags set within suspense boundaries that are sent later go at the end
+/// together with a small script that moves them into the head.
+/// Everything sent at the end, including the serialized state, goes before `</body>`
+/// if the document has one.
+/// Use [StringStream::deadline] or [StringStream::abort_handle] to stop waiting for slow components.
 pub struct StringStream<E> {
-    stack: Vec<(Option<String>, NodeStream<HtmlNode, E>)>,
+    doctype: Option<&'static str>,
+    root: Option<Segment<E>>,
+    boundaries: Boundaries<E>,
+    serialized_state: Option<SerializedState>,
+    options: SerializeOptions,
+    head: Option<Head>,
+    /// The tags that were inserted before `</head>`
+    head_tags: Option<Vec<HeadTag>>,
+    /// The output starting at `</head>` while the head tags are being collected
+    shell: Option<String>,
+    /// The output starting at `</body>`, which is sent once everything else is done
+    tail: Option<String>,
+    /// Output that has been rendered ahead, see [StringStream::render_shell]
+    rendered: VecDeque<String>,
+    abort: AbortHandle,
+    deadline: Option<Pin<Box<dyn Future<Output = ()> + Send>>>,
 }
 
 impl<E> Stream for StringStream<E> {
@@ -19,59 +56,353 @@ impl<E> Stream for StringStream<E> {
         mut self: std::pin::Pin<&mut Self>,
         cx: &mut std::task::Context<'_>,
     ) -> std::task::Poll<Option<Self::Item>> {
-        if let Some(stream) = self.stack.last_mut().map(|item| &mut item.1) {
-            match stream.poll_next_unpin(cx) {
-                Poll::Ready(Some(Ok((node, children)))) => match node {
-                    HtmlNode::Element(element) => {
-                        self.stack
-                            .push((Some(element.tag_name().to_string()), children));
-                        return Poll::Ready(Some(Ok(serialize_node_open(&element))));
-                    }
-                    HtmlNode::Text(text) => {
-                        return Poll::Ready(Some(Ok(text)));
-                    }
-                    HtmlNode::Comment(comment) => {
-                        return Poll::Ready(Some(Ok(format!("<!--{}-->", comment.text()))))
+        let this = &mut *self;
+
+        match this.rendered.pop_front() {
+            Some(chunk) => Poll::Ready(Some(Ok(chunk))),
+            None => this.poll_render(cx),
+        }
+    }
+}
+
+impl<E> StringStream<E> {
+    fn poll_render(&mut self, cx: &mut Context<'_>) -> Poll<Option<Result<String, E>>> {
+        let this = self;
+
+        if let Some(doctype) = this.doctype.take() {
+            return Poll::Ready(Some(Ok(doctype.to_string())));
+        }
+
+        if let Some(deadline) = this.deadline.as_mut() {
+            if deadline.poll_unpin(cx).is_ready() {
+                this.deadline = None;
+                this.abort.abort();
+            }
+        }
+        this.abort.register(cx.waker());
+        if this.abort.is_aborted() && (this.root.is_some() || !this.boundaries.is_empty()) {
+            let output = this.close();
+            if !output.is_empty() {
+                return Poll::Ready(Some(Ok(output)));
+            }
+        }
+
+        // Resolved boundaries must not overtake their fallback
+        if this.shell.is_none() {
+            if let Poll::Ready(resolved) = this.boundaries.poll_resolved(cx) {
+                return Poll::Ready(Some(resolved));
+            }
+        }
+
+        while let Some(root) = this.root.as_mut() {
+            match this.boundaries.poll_segment(root, cx) {
+                Poll::Ready(Some(Ok(chunk))) => {
+                    let closed = root.closed_element().map(str::to_string);
+                    if let Some(chunk) = this.hold_back(chunk, closed.as_deref()) {
+                        return Poll::Ready(Some(Ok(chunk)));
                     }
-                },
+                }
+                Poll::Ready(Some(Err(error))) => return Poll::Ready(Some(Err(error))),
                 Poll::Ready(None) => {
-                    if let Some((Some(tag_name), _)) = self.stack.pop() {
-                        Poll::Ready(Some(Ok(format!("</{}>", tag_name))))
-                    } else {
-                        Poll::Ready(None)
+                    this.root = None;
+                    if let Some(shell) = this.shell.take() {
+                        return Poll::Ready(Some(Ok(this.flush_head(shell))));
+                    }
+                    // New boundaries might have been registered by the last chunk
+                    if let Poll::Ready(resolved) = this.boundaries.poll_resolved(cx) {
+                        return Poll::Ready(Some(resolved));
                     }
                 }
-                Poll::Pending => Poll::Pending,
-                Poll::Ready(Some(Err(error))) => Poll::Ready(Some(Err(error))),
+                Poll::Pending => return Poll::Pending,
             }
-        } else {
+        }
+
+        if !this.boundaries.is_empty() {
+            return Poll::Pending;
+        }
+
+        let mut output = this.late_head().unwrap_or_default();
+        if let Some(state) = this.serialized_state.take() {
+            if !state.is_empty() {
+                output.push_str(&serialize_state(&state));
+            }
+        }
+        if let Some(tail) = this.tail.take() {
+            output.push_str(&tail);
+        }
+        if output.is_empty() {
             Poll::Ready(None)
+        } else {
+            Poll::Ready(Some(Ok(output)))
         }
     }
-}
 
-impl<E> StringStream<E> {
+    /// Render everything outside of pending suspense boundaries ahead,
+    /// so the response metadata set by components is known before the body is sent.
+    pub(crate) async fn render_shell(&mut self) -> Result<(), E> {
+        poll_fn(|cx| {
+            while self.root.is_some() {
+                match self.poll_render(cx) {
+                    Poll::Ready(Some(Ok(chunk))) => self.rendered.push_back(chunk),
+                    Poll::Ready(Some(Err(error))) => return Poll::Ready(Err(error)),
+                    Poll::Ready(None) => break,
+                    Poll::Pending => return Poll::Pending,
+                }
+            }
+            Poll::Ready(Ok(()))
+        })
+        .await
+    }
+
     pub fn new(root: NodeStream<HtmlNode, E>) -> Self {
+        Self::with_options(root, SerializeOptions::default())
+    }
+
+    pub fn with_options(root: NodeStream<HtmlNode, E>, options: SerializeOptions) -> Self {
+        let doctype = match (options.doctype, options.is_pretty()) {
+            (true, true) => Some("<!DOCTYPE html>\n"),
+            (true, false) => Some("<!DOCTYPE html>"),
+            (false, _) => None,
+        };
         Self {
-            stack: vec![(None, root)],
+            doctype,
+            root: Some(Segment::new(root, options)),
+            boundaries: Boundaries::new(),
+            serialized_state: None,
+            options,
+            head: None,
+            head_tags: None,
+            shell: None,
+            tail: None,
+            rendered: VecDeque::new(),
+            abort: AbortHandle::default(),
+            deadline: None,
         }
     }
+
+    /// Stop waiting for components once the given future resolves,
+    /// e.G. a timer of the async runtime:
+    /// ```ignore
+    /// render_to_stream(rsx!(<App />), spawner).deadline(tokio::time::sleep(Duration::from_secs(5)))
+    /// ```
+    /// See [AbortHandle] for what happens then.
+    pub fn deadline<D>(mut self, deadline: D) -> Self
+    where
+        D: Future<Output = ()> + Send + 'static,
+    {
+        self.deadline = Some(Box::pin(deadline));
+        self
+    }
+
+    /// Get a handle to stop waiting for components, e.G. once the client disconnected.
+    pub fn abort_handle(&self) -> AbortHandle {
+        self.abort.clone()
+    }
+
+    /// Keep the chunk in the shell or the tail if needed, otherwise it is sent right away.
+    fn hold_back(&mut self, chunk: String, closed: Option<&str>) -> Option<String> {
+        if let Some(tail) = self.tail.as_mut() {
+            tail.push_str(&chunk);
+        } else if closed == Some("body") {
+            self.tail = Some(chunk);
+        } else if let Some(shell) = self.shell.as_mut() {
+            shell.push_str(&chunk);
+        } else if self.head.is_some() && self.head_tags.is_none() && closed == Some("head") {
+            self.shell = Some(chunk);
+        } else {
+            return Some(chunk);
+        }
+        None
+    }
+
+    /// Close the document with what has been rendered so far.
+    fn close(&mut self) -> String {
+        let mut output = String::new();
+        if let Some(mut root) = self.root.take() {
+            while let Some(chunk) = root.close_next() {
+                if let Some(chunk) = self.hold_back(chunk, root.closed_element()) {
+                    output.push_str(&chunk);
+                }
+            }
+        }
+        self.boundaries.clear();
+        if let Some(shell) = self.shell.take() {
+            output.push_str(&self.flush_head(shell));
+        }
+        output
+    }
+
+    /// Coalesce the output into [bytes::Bytes] chunks of at least the given size,
+    /// see [BufferedStream].
+    pub fn buffered(self, chunk_size: usize) -> BufferedStream<Self, E> {
+        BufferedStream::new(self).chunk_size(chunk_size)
+    }
+
+    /// Render suspense boundaries in place instead of streaming them out of order,
+    /// e.G. if the output is not streamed anyways.
+    /// All tags set via `use_head` then end up in the head.
+    pub fn in_order(mut self) -> Self {
+        self.root = self.root.map(Segment::in_order);
+        self
+    }
+
+    /// Insert the recorded state before `</body>` or append it once the root stream is done.
+    pub(crate) fn with_serialized_state(mut self, serialized_state: SerializedState) -> Self {
+        self.serialized_state = Some(serialized_state);
+        self
+    }
+
+    /// Do not embed the values of `use_serialized_state` hooks,
+    /// e.G. for pages that are never hydrated.
+    pub fn without_serialized_state(mut self) -> Self {
+        self.serialized_state = None;
+        self
+    }
+
+    /// Insert the tags collected in the given [Head] before `</head>`.
+    pub(crate) fn with_head(mut self, head: Head) -> Self {
+        self.head = Some(head);
+        self
+    }
+
+    /// Prepend the head tags collected so far to the output starting at `</head>`.
+    fn flush_head(&mut self, shell: String) -> String {
+        let tags = match self.head.as_ref() {
+            Some(head) => head.tags(),
+            None => return shell,
+        };
+        let indent = &shell[..shell.len() - shell.trim_start().len()];
+        let indent = if indent.is_empty() {
+            String::new()
+        } else {
+            format!("{}  ", indent)
+        };
+        let output = serialize_head(&tags, &indent, &self.options) + &shell;
+        self.head_tags = Some(tags);
+        output
+    }
+
+    /// The tags that were set after `</head>` has been sent.
+    fn late_head(&mut self) -> Option<String> {
+        let head_tags = self.head_tags.take()?;
+        let late = self
+            .head
+            .take()?
+            .tags()
+            .into_iter()
+            .filter(|tag| !head_tags.contains(tag))
+            .collect::<Vec<_>>();
+        (!late.is_empty()).then(|| serialize_late_head(&late, &self.options))
+    }
+}
+
+/// Provide a recording [SerializedState] to the rendered tree
+/// so values of `use_serialized_state` can be embedded in the output.
+pub(crate) fn with_serialized_state<E>(
+    element: Element<HtmlNode, E>,
+    serialized_state: &SerializedState,
+) -> Element<HtmlNode, E> {
+    Provider::new(serialized_state.clone()).children(vec![element])
+}
+
+/// Provide a [Head] to the rendered tree to collect the tags set via `use_head`.
+pub(crate) fn with_head<E>(element: Element<HtmlNode, E>, head: &Head) -> Element<HtmlNode, E> {
+    Provider::new(head.clone()).children(vec![element])
 }
 
 /// use the render_to_stream API to render a component to a stream of strings.
 /// This works very nicely with the axum-framework making it easy to implement
 /// natively streaming server-side rendering.
+/// Values of `use_serialized_state` hooks are embedded before `</body>`,
+/// or at the end of the output if there is none, so `bloom_client::hydrate` can pick them up.
+/// Tags set via `use_head` are rendered into the `<head>` of the document.
+/// The output of components and fragments as well as adjacent text nodes are
+/// separated by comments so hydration can match every node.
 pub fn render_to_stream<E, S>(element: Element<HtmlNode, E>, spawner: S) -> StringStream<E>
 where
     E: Send + 'static,
     S: Spawn + Clone + Send + 'static,
 {
-    StringStream::new(render_stream(element, spawner))
+    render_to_stream_with_options(element, spawner, SerializeOptions::default())
+}
+
+/// Like [render_to_stream] but the output is configured using [SerializeOptions].
+pub fn render_to_stream_with_options<E, S>(
+    element: Element<HtmlNode, E>,
+    spawner: S,
+    options: SerializeOptions,
+) -> StringStream<E>
+where
+    E: Send + 'static,
+    S: Spawn + Clone + Send + 'static,
+{
+    let serialized_state = SerializedState::record();
+    let head = Head::new();
+    StringStream::with_options(
+        render_stream(
+            with_serialized_state(with_head(element, &head), &serialized_state),
+            spawner,
+        ),
+        options,
+    )
+    .with_serialized_state(serialized_state)
+    .with_head(head)
+}
+
+/// Render a document for a request.
+/// The context is seeded with the given providers, e.G. with the request URL, headers or locale,
+/// and with a [ResponseHandle] that components use to set the status code, headers or a redirect.
+/// The returned future resolves once everything outside of pending suspense boundaries is rendered,
+/// so the response metadata is known before the body starts streaming.
+/// Changes to the response made within suspense boundaries that are still pending are not reflected.
+/// ```ignore
+/// let (response, body) = render_to_stream_with_context(
+///     rsx!(<App />),
+///     spawner,
+///     vec![Provider::new(RequestUrl(uri.to_string()))],
+///     SerializeOptions::new().doctype(true),
+/// )
+/// .await?;
+///
+/// let mut builder = Response::builder().status(response.status());
+/// for (key, value) in response.headers() {
+///     builder = builder.header(key, value);
+/// }
+/// builder.body(Body::from_stream(body))
+/// ```
+pub async fn render_to_stream_with_context<E, S>(
+    element: Element<HtmlNode, E>,
+    spawner: S,
+    context: Vec<Provider>,
+    options: SerializeOptions,
+) -> Result<(ResponseHandle, StringStream<E>), E>
+where
+    E: Send + 'static,
+    S: Spawn + Clone + Send + 'static,
+{
+    let response = ResponseHandle::new();
+    let element = context
+        .into_iter()
+        .rev()
+        .fold(element, |element, provider| {
+            provider.children(vec![element])
+        });
+    let element = Provider::new(response.clone()).children(vec![element]);
+
+    let mut stream = render_to_stream_with_options(element, spawner, options);
+    stream.render_shell().await?;
+    Ok((response, stream))
 }
 
 #[cfg(test)]
 mod tests {
-    use bloom_html::{tag::div, text};
+    use std::sync::Arc;
+
+    use async_channel::Receiver;
+    use async_trait::async_trait;
+    use bloom_core::{Component, Suspense};
+    use bloom_html::{tag, tag::div, text};
+    use futures_util::StreamExt;
 
     use crate::spawner::TokioSpawner;
 
@@ -102,7 +433,439 @@ mod tests {
             output.push_str(&chunk);
         }
 
-        assert!(output.contains("class=\"foo\""));
-        assert!(output.contains("id=\"bar\""));
+        assert_eq!(output, "<div class=\"foo\" id=\"bar\"></div>");
+    }
+
+    #[tokio::test]
+    async fn render_properties_as_attributes() {
+        let element = div().build().children(vec![
+            tag("input")
+                .attr("type", "checkbox")
+                .prop("checked", true)
+                .prop("indeterminate", false)
+                .build()
+                .into(),
+            tag("input").prop("value", "a \"b\"").build().into(),
+            tag("option").prop("selected", false).build().into(),
+            tag("textarea").prop("value", "</textarea>").build().into(),
+        ]);
+
+        let mut stream = render_to_stream::<(), TokioSpawner>(element, TokioSpawner);
+
+        let mut output = String::new();
+        while let Some(Ok(chunk)) = stream.next().await {
+            output.push_str(&chunk);
+        }
+
+        assert_eq!(
+            output,
+            "<div><input type=\"checkbox\" checked><input value=\"a &quot;b&quot;\"><option></option><textarea>&lt;/textarea&gt;</textarea></div>"
+        );
+    }
+
+    #[tokio::test]
+    async fn select_option_by_value() {
+        let option = |value: &str| {
+            tag("option")
+                .prop("value", value)
+                .build()
+                .children(vec![text(value)])
+        };
+        let element = div().build().children(vec![
+            tag("select").prop("value", "b").build().children(vec![
+                option("a"),
+                tag("optgroup").build().children(vec![option("b")]),
+            ]),
+            tag("li").prop("value", 3).build().into(),
+            tag("progress").prop("value", 0.5).build().into(),
+        ]);
+
+        let stream = render_to_stream::<(), TokioSpawner>(element, TokioSpawner);
+
+        let output = stream.map(|chunk| chunk.unwrap()).collect::<String>().await;
+        assert_eq!(
+            output,
+            "<div><select><option value=\"a\">a</option><optgroup><option value=\"b\" selected>b</option></optgroup></select><li value=\"3\"></li><progress value=\"0.5\"></progress></div>"
+        );
+    }
+
+    #[tokio::test]
+    async fn stream_suspense_out_of_order() {
+        struct Slow(Receiver<()>);
+
+        impl PartialEq for Slow {
+            fn eq(&self, _other: &Self) -> bool {
+                true
+            }
+        }
+
+        #[async_trait]
+        impl Component for Slow {
+            type Node = HtmlNode;
+            type Error = ();
+
+            async fn render(
+                self: Arc<Self>,
+            ) -> Result<Element<Self::Node, Self::Error>, Self::Error> {
+                let _ = self.0.recv().await;
+                Ok(text("slow"))
+            }
+        }
+
+        let (sender, receiver) = async_channel::bounded(1);
+        let element = div().build().children(vec![
+            Suspense::new(|| text("loading")).children(vec![text("fast")]),
+            Suspense::new(|| text("loading"))
+                .children(vec![Element::Component(Arc::new(Slow(receiver)))]),
+            text("after"),
+        ]);
+
+        let mut stream = render_to_stream::<(), TokioSpawner>(element, TokioSpawner);
+
+        let mut output = String::new();
+        while !output.ends_with("</div>") {
+            output.push_str(&stream.next().await.unwrap().unwrap());
+        }
+
+        assert_eq!(
+            output,
+            "<div>fast<template id=\"bloom-b0\"></template>loading<!--/bloom-b0-->after</div>"
+        );
+
+        sender.send(()).await.unwrap();
+
+        let mut output = String::new();
+        while let Some(Ok(chunk)) = stream.next().await {
+            output.push_str(&chunk);
+        }
+
+        assert!(
+            output.starts_with("<template id=\"bloom-s0\"><!--[-->slow<!--]--></template><script>")
+        );
+        assert!(output.ends_with("bloomSwap(0)</script>"));
+    }
+
+    #[tokio::test]
+    async fn render_head_tags() {
+        #[derive(PartialEq)]
+        struct Page;
+
+        #[async_trait]
+        impl Component for Page {
+            type Node = HtmlNode;
+            type Error = ();
+
+            async fn render(
+                self: Arc<Self>,
+            ) -> Result<Element<Self::Node, Self::Error>, Self::Error> {
+                bloom_html::use_head(vec![
+                    bloom_html::HeadTag::title("Page & more"),
+                    bloom_html::HeadTag::meta("description", "A page"),
+                ]);
+                Ok(text("content"))
+            }
+        }
+
+        let element = bloom_html::tag("html").build().children(vec![
+            bloom_html::tag("head")
+                .build()
+                .children(vec![bloom_html::tag("meta")
+                    .attr("charset", "utf-8")
+                    .build()
+                    .into()]),
+            bloom_html::tag("body")
+                .build()
+                .children(vec![Element::Component(Arc::new(Page))]),
+        ]);
+
+        let mut stream = render_to_stream::<(), TokioSpawner>(element, TokioSpawner).in_order();
+
+        let mut output = String::new();
+        while let Some(Ok(chunk)) = stream.next().await {
+            output.push_str(&chunk);
+        }
+
+        assert_eq!(
+            output,
+            "<html><head><meta charset=\"utf-8\"><title data-bloom-head=\"title\">Page &amp; more</title><meta name=\"description\" content=\"A page\" data-bloom-head=\"meta:description\"></head><body><!--[-->content<!--]--></body></html>"
+        );
+    }
+
+    #[tokio::test]
+    async fn render_head_tags_pretty() {
+        #[derive(PartialEq)]
+        struct Page;
+
+        #[async_trait]
+        impl Component for Page {
+            type Node = HtmlNode;
+            type Error = ();
+
+            async fn render(
+                self: Arc<Self>,
+            ) -> Result<Element<Self::Node, Self::Error>, Self::Error> {
+                bloom_html::use_head(vec![bloom_html::HeadTag::title("Page")]);
+                Ok(text("content"))
+            }
+        }
+
+        let element = tag("html").build().children(vec![
+            tag("head")
+                .build()
+                .children(vec![tag("meta").attr("charset", "utf-8").build().into()]),
+            tag("body")
+                .build()
+                .children(vec![Element::Component(Arc::new(Page))]),
+        ]);
+
+        let stream = render_to_stream_with_options::<(), TokioSpawner>(
+            element,
+            TokioSpawner,
+            SerializeOptions::new().pretty(true),
+        );
+
+        let output = stream.map(|chunk| chunk.unwrap()).collect::<String>().await;
+        assert!(output.starts_with(
+            "<html>\n  <head>\n    <meta charset=\"utf-8\">\n    <title data-bloom-head=\"title\">Page</title>\n  </head>"
+        ));
+    }
+
+    #[tokio::test]
+    async fn stream_body_before_suspended_head_tags() {
+        struct SlowPage(Receiver<()>);
+
+        impl PartialEq for SlowPage {
+            fn eq(&self, _other: &Self) -> bool {
+                true
+            }
+        }
+
+        #[async_trait]
+        impl Component for SlowPage {
+            type Node = HtmlNode;
+            type Error = ();
+
+            async fn render(
+                self: Arc<Self>,
+            ) -> Result<Element<Self::Node, Self::Error>, Self::Error> {
+                bloom_core::use_serialized_state(|| 1);
+                let _ = self.0.recv().await;
+                bloom_html::use_head(vec![bloom_html::HeadTag::title("Slow")]);
+                Ok(text("slow"))
+            }
+        }
+
+        #[derive(PartialEq)]
+        struct Page;
+
+        #[async_trait]
+        impl Component for Page {
+            type Node = HtmlNode;
+            type Error = ();
+
+            async fn render(
+                self: Arc<Self>,
+            ) -> Result<Element<Self::Node, Self::Error>, Self::Error> {
+                bloom_html::use_head(vec![
+                    bloom_html::HeadTag::title("Page"),
+                    bloom_html::HeadTag::meta("description", "A page"),
+                ]);
+                Ok(div().build().children(vec![text("fast")]))
+            }
+        }
+
+        #[derive(PartialEq)]
+        struct Layout;
+
+        #[async_trait]
+        impl Component for Layout {
+            type Node = HtmlNode;
+            type Error = ();
+
+            async fn render(
+                self: Arc<Self>,
+            ) -> Result<Element<Self::Node, Self::Error>, Self::Error> {
+                bloom_html::use_head(vec![bloom_html::HeadTag::title("Layout")]);
+                Ok(bloom_html::tag("link")
+                    .attr("rel", "stylesheet")
+                    .attr("href", "/style.css")
+                    .build()
+                    .into())
+            }
+        }
+
+        let (sender, receiver) = async_channel::bounded(1);
+        let element = bloom_html::tag("html").build().children(vec![
+            bloom_html::tag("head")
+                .build()
+                .children(vec![Element::Component(Arc::new(Layout))]),
+            bloom_html::tag("body").build().children(vec![
+                Element::Component(Arc::new(Page)),
+                Suspense::new(|| text("loading"))
+                    .children(vec![Element::Component(Arc::new(SlowPage(receiver)))]),
+            ]),
+        ]);
+
+        let mut stream = render_to_stream::<(), TokioSpawner>(element, TokioSpawner);
+
+        let mut output = String::new();
+        while !output.ends_with("<!--/bloom-b0-->") {
+            output.push_str(&stream.next().await.unwrap().unwrap());
+        }
+
+        assert_eq!(
+            output,
+            "<html><head><!--[--><link rel=\"stylesheet\" href=\"/style.css\"><!--]--><title data-bloom-head=\"title\">Page</title><meta name=\"description\" content=\"A page\" data-bloom-head=\"meta:description\"></head><body><!--[--><div>fast</div><!--]--><template id=\"bloom-b0\"></template>loading<!--/bloom-b0-->"
+        );
+
+        sender.send(()).await.unwrap();
+
+        let mut output = String::new();
+        while let Some(Ok(chunk)) = stream.next().await {
+            output.push_str(&chunk);
+        }
+
+        assert!(
+            output.starts_with("<template id=\"bloom-s0\"><!--[-->slow<!--]--></template><script>")
+        );
+        assert!(output.contains("<template id=\"bloom-head\"><title data-bloom-head=\"title\">Slow</title></template><script>"));
+        assert!(output.contains("<script type=\"application/json\" id=\"bloom-serialized-state\">"));
+        assert!(output.ends_with("</script></body></html>"));
+    }
+
+    #[tokio::test]
+    async fn render_with_context() {
+        #[derive(Clone, Default)]
+        struct RequestPath(String);
+
+        #[derive(PartialEq)]
+        struct NotFound;
+
+        #[async_trait]
+        impl Component for NotFound {
+            type Node = HtmlNode;
+            type Error = ();
+
+            async fn render(
+                self: Arc<Self>,
+            ) -> Result<Element<Self::Node, Self::Error>, Self::Error> {
+                let path = bloom_core::use_context::<RequestPath>();
+                let response = bloom_core::use_context::<ResponseHandle>();
+                response.set_status(404);
+                response.insert_header("Cache-Control", "no-store");
+                Ok(text(format!("{} not found", path.0)))
+            }
+        }
+
+        let (response, stream) = render_to_stream_with_context::<(), TokioSpawner>(
+            div()
+                .build()
+                .children(vec![Element::Component(Arc::new(NotFound))]),
+            TokioSpawner,
+            vec![Provider::new(RequestPath("/foo".to_string()))],
+            SerializeOptions::new(),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(response.status(), 404);
+        assert_eq!(
+            response.headers(),
+            vec![("Cache-Control".to_string(), "no-store".to_string())]
+        );
+
+        let output = stream.map(|chunk| chunk.unwrap()).collect::<String>().await;
+        assert_eq!(output, "<div><!--[-->/foo not found<!--]--></div>");
+    }
+
+    #[tokio::test]
+    async fn render_head_tags_with_context() {
+        #[derive(PartialEq)]
+        struct Page;
+
+        #[async_trait]
+        impl Component for Page {
+            type Node = HtmlNode;
+            type Error = ();
+
+            async fn render(
+                self: Arc<Self>,
+            ) -> Result<Element<Self::Node, Self::Error>, Self::Error> {
+                bloom_html::use_head(vec![bloom_html::HeadTag::title("Page")]);
+                Ok(text("page"))
+            }
+        }
+
+        let element = tag("html").build().children(vec![
+            tag("head").build().into(),
+            tag("body")
+                .build()
+                .children(vec![Element::Component(Arc::new(Page))]),
+        ]);
+        let (_, stream) = render_to_stream_with_context::<(), TokioSpawner>(
+            element,
+            TokioSpawner,
+            Vec::new(),
+            SerializeOptions::new(),
+        )
+        .await
+        .unwrap();
+
+        let output = stream.map(|chunk| chunk.unwrap()).collect::<String>().await;
+        assert_eq!(
+            output,
+            "<html><head><title data-bloom-head=\"title\">Page</title></head><body><!--[-->page<!--]--></body></html>"
+        );
+    }
+
+    #[tokio::test]
+    async fn close_at_deadline() {
+        struct Hung;
+
+        impl PartialEq for Hung {
+            fn eq(&self, _other: &Self) -> bool {
+                true
+            }
+        }
+
+        #[async_trait]
+        impl Component for Hung {
+            type Node = HtmlNode;
+            type Error = ();
+
+            async fn render(
+                self: Arc<Self>,
+            ) -> Result<Element<Self::Node, Self::Error>, Self::Error> {
+                futures_util::future::pending::<()>().await;
+                Ok(text("never"))
+            }
+        }
+
+        let element = div().build().children(vec![
+            Suspense::new(|| text("loading")).children(vec![Element::Component(Arc::new(Hung))]),
+            div()
+                .build()
+                .children(vec![Element::Component(Arc::new(Hung))]),
+        ]);
+
+        let (sender, receiver) = async_channel::bounded::<()>(1);
+        let stream =
+            render_to_stream::<(), TokioSpawner>(element, TokioSpawner).deadline(async move {
+                let _ = receiver.recv().await;
+            });
+        let abort = stream.abort_handle();
+
+        tokio::spawn(async move {
+            tokio::task::yield_now().await;
+            sender.send(()).await.unwrap();
+        });
+
+        let output = stream.map(|chunk| chunk.unwrap()).collect::<String>().await;
+
+        assert!(abort.is_aborted());
+        assert_eq!(
+            output,
+            "<div><template id=\"bloom-b0\"></template>loading<!--/bloom-b0--><div><!--?--></div><!--?--></div>"
+        );
     }
 }
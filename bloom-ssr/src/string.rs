@@ -1,48 +1,48 @@
-use bloom_core::{render_stream, Element};
+use bloom_core::Element;
 use bloom_html::HtmlNode;
 use futures_util::{task::Spawn, StreamExt};
 
+use crate::{render_to_stream_with_options, SerializeOptions};
+
 /// render_to_string takes a bloom-core Element and a spawner and returns a string.
 /// Prefer using render_to_stream where possible to get the advantages of streaming rendering.
 /// This function is useful for testing and other use-cases where you need the full string at once,
 /// e.G. if the necessary headers cannot be sent before the full body is rendered.
+/// The output is serialized just like the output of render_to_stream,
+/// except that suspense boundaries are rendered in place.
 pub async fn render_to_string<E, S>(element: Element<HtmlNode, E>, spawner: S) -> Result<String, E>
 where
     E: Send + 'static,
     S: Spawn + Send + Clone + 'static,
 {
-    let mut output = String::new();
+    render_to_string_with_options(element, spawner, SerializeOptions::default()).await
+}
 
-    let mut stack = vec![(None, render_stream(element, spawner))];
-
-    while let Some((_, stream)) = stack.last_mut() {
-        match stream.next().await {
-            Some(Ok((node, children))) => match node {
-                HtmlNode::Element(element) => {
-                    stack.push((Some(element.tag_name().to_string()), children));
-                    output.push_str(&format!("<{}>", element.tag_name()));
-                }
-                HtmlNode::Text(text) => {
-                    output.push_str(&text);
-                }
-                HtmlNode::Comment(comment) => {
-                    output.push_str(&format!("<!--{}-->", comment.text()));
-                }
-            },
-            Some(Err(error)) => return Err(error),
-            None => {
-                if let Some((Some(tag_name), _)) = stack.pop() {
-                    output.push_str(&format!("</{}>", tag_name));
-                }
-            }
-        }
+/// Like [render_to_string] but the output is configured using [SerializeOptions].
+pub async fn render_to_string_with_options<E, S>(
+    element: Element<HtmlNode, E>,
+    spawner: S,
+    options: SerializeOptions,
+) -> Result<String, E>
+where
+    E: Send + 'static,
+    S: Spawn + Send + Clone + 'static,
+{
+    let mut stream = render_to_stream_with_options(element, spawner, options).in_order();
+    let mut output = String::new();
+    while let Some(chunk) = stream.next().await {
+        output.push_str(&chunk?);
     }
-
     Ok(output)
 }
 
 #[cfg(test)]
 mod tests {
+    use std::sync::Arc;
+
+    use async_trait::async_trait;
+    use bloom_core::{use_resource, use_serialized_state, Component, Resource};
+
     use crate::spawner::TokioSpawner;
 
     use super::*;
@@ -57,4 +57,163 @@ mod tests {
 
         assert_eq!(output, Ok("<div>foo</div>".to_string()));
     }
+
+    #[tokio::test]
+    async fn render_escaped_string() {
+        let element = bloom_html::tag::div().build().children(vec![
+            bloom_html::text("<b>&</b>"),
+            bloom_html::tag("br").build().into(),
+            bloom_html::tag::script()
+                .build()
+                .children(vec![bloom_html::text("a < b && '</script>'")]),
+        ]);
+
+        let output = render_to_string::<(), TokioSpawner>(element, TokioSpawner).await;
+
+        assert_eq!(
+            output,
+            Ok(
+                "<div>&lt;b&gt;&amp;&lt;/b&gt;<br><script>a < b && '<\\/script>'</script></div>"
+                    .to_string()
+            )
+        );
+    }
+
+    #[tokio::test]
+    async fn embed_serialized_state() {
+        #[derive(PartialEq)]
+        struct Greeting;
+
+        #[async_trait]
+        impl Component for Greeting {
+            type Node = HtmlNode;
+            type Error = ();
+
+            async fn render(
+                self: Arc<Self>,
+            ) -> Result<Element<Self::Node, Self::Error>, Self::Error> {
+                let name = use_serialized_state(|| None::<String>);
+                let loaded = match name.as_ref() {
+                    Some(name) => name.clone(),
+                    None => {
+                        let loaded = "</script>".to_string();
+                        let value = loaded.clone();
+                        name.update(move |_| Some(value));
+                        loaded
+                    }
+                };
+                Ok(bloom_html::text(loaded))
+            }
+        }
+
+        let element = bloom_html::tag::div()
+            .build()
+            .children(vec![Element::Component(Arc::new(Greeting))]);
+
+        let output = render_to_string::<(), TokioSpawner>(element, TokioSpawner).await;
+
+        assert_eq!(
+            output,
+            Ok("<div><!--[-->&lt;/script&gt;<!--]--></div><script type=\"application/json\" id=\"bloom-serialized-state\">{\"/0:0\":\"\\u003c/script>\"}</script>".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn await_resource() {
+        #[derive(PartialEq)]
+        struct Data;
+
+        #[async_trait]
+        impl Component for Data {
+            type Node = HtmlNode;
+            type Error = ();
+
+            async fn render(
+                self: Arc<Self>,
+            ) -> Result<Element<Self::Node, Self::Error>, Self::Error> {
+                let data = use_resource((), async {
+                    tokio::task::yield_now().await;
+                    Ok::<_, ()>("data".to_string())
+                })
+                .await;
+                let after = use_serialized_state(|| 1);
+                Ok(match data.as_ref() {
+                    Resource::Ready(data) => bloom_html::text(format!("{} {}", data, *after)),
+                    _ => bloom_html::text("loading"),
+                })
+            }
+        }
+
+        let output =
+            render_to_string::<(), TokioSpawner>(Element::Component(Arc::new(Data)), TokioSpawner)
+                .await;
+
+        let output = output.unwrap();
+        assert!(output.starts_with("<!--[-->data 1<!--]--><script"));
+        // The value after the resource keeps its hook index
+        assert!(output.contains("\"/0:0\":\"data\""));
+        assert!(output.contains("\"/0:2\":1"));
+    }
+
+    #[tokio::test]
+    async fn render_with_options() {
+        let element = || {
+            bloom_html::tag::div().build().children(vec![
+                bloom_html::tag("p")
+                    .build()
+                    .children(vec![bloom_html::text("foo   bar")]),
+                HtmlNode::comment("note".to_string()).build().into(),
+                bloom_html::tag("input").attr("checked", "").build().into(),
+                bloom_html::tag("pre")
+                    .build()
+                    .children(vec![bloom_html::text("  a\n  b")]),
+            ])
+        };
+
+        let pretty = render_to_string_with_options::<(), TokioSpawner>(
+            element(),
+            TokioSpawner,
+            SerializeOptions::new().doctype(true).pretty(true),
+        )
+        .await;
+        assert_eq!(
+            pretty,
+            Ok("<!DOCTYPE html>\n<div>\n  <p>\n    foo   bar\n  </p>\n  <!--note-->\n  <input checked>\n  <pre>  a\n  b</pre>\n</div>".to_string())
+        );
+
+        let minified = render_to_string_with_options::<(), TokioSpawner>(
+            element(),
+            TokioSpawner,
+            SerializeOptions::new().minify(true).xhtml(true),
+        )
+        .await;
+        assert_eq!(
+            minified,
+            Ok(
+                "<div><p>foo bar</p><input checked=\"checked\" /><pre>  a\n  b</pre></div>"
+                    .to_string()
+            )
+        );
+    }
+
+    #[tokio::test]
+    async fn render_hydration_markers() {
+        let element = bloom_html::tag::div().build().children(vec![
+            bloom_html::text("Count: "),
+            Element::Fragment(vec![bloom_html::text(1), bloom_html::text("")]),
+            bloom_html::tag("textarea")
+                .build()
+                .children(vec![Element::Fragment(vec![
+                    bloom_html::text("a"),
+                    bloom_html::text("b"),
+                ])]),
+        ]);
+
+        let output = render_to_string::<(), TokioSpawner>(element, TokioSpawner).await;
+
+        assert_eq!(
+            output,
+            Ok("<div>Count: <!--[-->1<!--|--><!--]--><textarea>ab</textarea></div>".to_string())
+        );
+    }
 }